@@ -102,6 +102,7 @@ impl<'cs, T> Drop for LockGuard<'cs, T> {
 unsafe impl<T> Sync for Mutex<T> where T: Send {}
 
 #[cfg(test)]
+#[allow(clippy::redundant_pattern_matching)]
 mod test_mutex {
     use super::*;
 
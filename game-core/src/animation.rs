@@ -0,0 +1,151 @@
+//! Keyframe animations, advanced by the game tick.
+use crate::Duration;
+
+/// What to show, and for how long.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Keyframe<K> {
+    pub key: K,
+    pub duration: Duration,
+}
+
+impl<K> Keyframe<K> {
+    pub const fn new(key: K, duration: Duration) -> Self {
+        Self { key, duration }
+    }
+}
+
+/// Whether an animation starts over after its last keyframe.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Repeat {
+    /// stop on the last keyframe.
+    Once,
+    Loop,
+}
+
+/// An animation playing keyframes one after another.
+///
+/// the keys are drawn by the user of the animation, they can be
+/// whole [`Frame`](crate::Frame)s, or what to draw in the current state.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Animation<K: 'static> {
+    keyframes: &'static [Keyframe<K>],
+    repeat: Repeat,
+    /// the current keyframe.
+    index: u8,
+    /// ticks into the current keyframe.
+    cnt: u32,
+    finished: bool,
+}
+
+impl<K: Copy> Animation<K> {
+    /// start at the first of `keyframes`, which can not be empty.
+    pub const fn new(keyframes: &'static [Keyframe<K>], repeat: Repeat) -> Self {
+        assert!(!keyframes.is_empty());
+        Self {
+            keyframes,
+            repeat,
+            index: 0,
+            cnt: 0,
+            finished: false,
+        }
+    }
+
+    /// advance by one tick.
+    ///
+    /// returns true at the end of the last keyframe,
+    /// when a loop starts over or a one-shot finishes.
+    pub fn poll(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+        self.cnt += 1;
+        if self.cnt < self.keyframes[self.index as usize].duration.ticks() {
+            return false;
+        }
+        if self.index as usize + 1 < self.keyframes.len() {
+            self.index += 1;
+            self.cnt = 0;
+            return false;
+        }
+        match self.repeat {
+            Repeat::Once => self.finished = true,
+            Repeat::Loop => {
+                self.index = 0;
+                self.cnt = 0;
+            }
+        }
+        true
+    }
+
+    /// the key of the current keyframe, the last one once finished.
+    #[inline]
+    pub fn key(&self) -> K {
+        self.keyframes[self.index as usize].key
+    }
+
+    /// whether a one-shot animation has played all its keyframes.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// play from the first keyframe again.
+    #[inline]
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.cnt = 0;
+        self.finished = false;
+    }
+}
+
+#[cfg(test)]
+mod test_animation {
+    use super::*;
+
+    const BLINK: [Keyframe<bool>; 2] = [
+        Keyframe::new(true, Duration::from_ticks(2)),
+        Keyframe::new(false, Duration::from_ticks(3)),
+    ];
+
+    fn play(animation: &mut Animation<bool>, ticks: usize) -> Vec<(bool, bool)> {
+        (0..ticks)
+            .map(|_| {
+                let end = animation.poll();
+                (animation.key(), end)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_loop() {
+        let mut animation = Animation::new(&BLINK, Repeat::Loop);
+        assert!(animation.key());
+        let keys: Vec<_> = play(&mut animation, 10)
+            .iter()
+            .map(|(key, _)| *key)
+            .collect();
+        assert_eq!(
+            keys,
+            [true, false, false, false, true, true, false, false, false, true]
+        );
+        assert!(!animation.is_finished());
+        let ends = play(&mut animation, 10);
+        assert_eq!(ends.iter().filter(|(_, end)| *end).count(), 2);
+    }
+
+    #[test]
+    fn test_once() {
+        let mut animation = Animation::new(&BLINK, Repeat::Once);
+        let ends = play(&mut animation, 5);
+        assert_eq!(ends[4], (false, true));
+        assert!(ends[..4].iter().all(|(_, end)| !end));
+        assert!(animation.is_finished());
+
+        // stays on the last keyframe
+        assert_eq!(play(&mut animation, 3), [(false, false); 3]);
+
+        animation.restart();
+        assert!(animation.key());
+        assert!(!animation.is_finished());
+    }
+}
@@ -0,0 +1,106 @@
+//! A computer opponent, pressing button B.
+use crate::RandomSource;
+
+/// Tap rate of the computer opponent.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy = 0,
+    Medium = 1,
+    Hard = 2,
+}
+
+impl Difficulty {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Difficulty::Easy),
+            1 => Some(Difficulty::Medium),
+            2 => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// presses per second.
+    #[inline]
+    pub fn taps_per_sec(self) -> u8 {
+        match self {
+            Difficulty::Easy => 4,
+            Difficulty::Medium => 6,
+            Difficulty::Hard => 9,
+        }
+    }
+
+    /// average ticks (256HZ) between presses.
+    #[inline]
+    fn interval(self) -> u8 {
+        (256 / self.taps_per_sec() as u16) as u8
+    }
+}
+
+/// Generates the presses of the computer opponent.
+///
+/// presses are spaced by the interval of the difficulty,
+/// give or take a quarter of it.
+#[derive(Default)]
+pub struct Bot {
+    /// ticks until the next press.
+    wait: u8,
+}
+
+impl Bot {
+    pub const fn new() -> Self {
+        Self { wait: 0 }
+    }
+
+    /// wait for a full interval before the first press,
+    /// call when the bot should not press.
+    #[inline]
+    pub fn reset(&mut self, difficulty: Difficulty) {
+        self.wait = difficulty.interval();
+    }
+
+    /// call once per tick, returns true if the bot presses.
+    pub fn poll(&mut self, difficulty: Difficulty, rng: &mut impl RandomSource) -> bool {
+        if self.wait > 0 {
+            self.wait -= 1;
+            return false;
+        }
+        let interval = difficulty.interval() as u16;
+        let jitter = interval / 4;
+        // interval - jitter ..= interval + jitter
+        let offset = rng.random_u8() as u16 * (2 * jitter + 1) / 256;
+        self.wait = (interval - jitter + offset - 1) as u8;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test_bot {
+    use super::*;
+    use crate::fake::FakeRng;
+
+    /// ticks between the first presses.
+    fn intervals(difficulty: Difficulty, random: u8) -> Vec<u32> {
+        let mut bot = Bot::new();
+        let mut rng = FakeRng(random);
+        bot.reset(difficulty);
+        let presses = (0..2048u32)
+            .filter(|_| bot.poll(difficulty, &mut rng))
+            .collect::<Vec<_>>();
+        presses.windows(2).map(|w| w[1] - w[0]).collect()
+    }
+
+    #[test]
+    fn test_tap_rate() {
+        for (difficulty, min, max) in [
+            (Difficulty::Easy, 48, 80),
+            (Difficulty::Medium, 32, 52),
+            (Difficulty::Hard, 21, 35),
+        ] {
+            assert!(intervals(difficulty, 0).iter().all(|&t| t == min));
+            assert!(intervals(difficulty, 255).iter().all(|&t| t == max));
+            let average = 256 / difficulty.taps_per_sec() as u32;
+            assert!(intervals(difficulty, 128).iter().all(|&t| t == average));
+        }
+    }
+}
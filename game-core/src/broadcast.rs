@@ -0,0 +1,224 @@
+//! Broadcast of the live match, mirrored by spectator boards.
+//!
+//! The board running the game sends a [`Snapshot`] of the match every
+//! [`INTERVAL`], which a spectator draws on its own matrix.
+//!
+//! Packet format: `b'S'`, the version byte, the state, the row and col
+//! of the dot (both `NO_DOT` without a dot), the wins of A and B, and
+//! the winner of the round shown: 0 for none, 1 for A, 2 for B.
+use crate::{draw_glyph, Duration, Frame, Game, Match, Players, Score};
+
+pub const VERSION: u8 = 1;
+/// tells snapshots apart from the frames of the remote boards.
+const MAGIC: u8 = b'S';
+/// bytes of a packet.
+pub const PACKET_LEN: usize = 8;
+/// row and col of the missing dot.
+const NO_DOT: u8 = 0xff;
+/// time between the snapshots.
+pub const INTERVAL: Duration = Duration::from_ms(100);
+/// a spectator clears its matrix after missing snapshots for this long.
+pub const SIGNAL_TIMEOUT: Duration = Duration::from_secs(1);
+/// cols of the wins of A and B, as on the board.
+const SCORE_COLS: [u8; 2] = [0, 4];
+/// col of the letter of the round winner.
+const WINNER_COL: i16 = 1;
+
+/// The state of the game, without its animations.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum State {
+    Idle,
+    Ready,
+    Playing,
+    Result,
+    FalseStart,
+    Settings,
+    Paused,
+}
+
+impl State {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(State::Idle),
+            1 => Some(State::Ready),
+            2 => Some(State::Playing),
+            3 => Some(State::Result),
+            4 => Some(State::FalseStart),
+            5 => Some(State::Settings),
+            6 => Some(State::Paused),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Game> for State {
+    fn from(game: &Game) -> Self {
+        match game {
+            Game::IdleAnimation { .. } => State::Idle,
+            Game::ReadyAnimation { .. } => State::Ready,
+            Game::Playing { .. } => State::Playing,
+            Game::Result { .. } => State::Result,
+            Game::FalseStart { .. } => State::FalseStart,
+            Game::SettingsMenu { .. } => State::Settings,
+            Game::Paused { .. } => State::Paused,
+        }
+    }
+}
+
+/// Malformed packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnapshotError {
+    /// not a snapshot, e.g. a frame of a remote board.
+    Magic,
+    /// snapshot of another version of the format.
+    Version(u8),
+    /// wrong length, or a field out of range.
+    Field,
+}
+
+/// The state of the match shown to spectators.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Snapshot {
+    pub state: State,
+    /// (row, col) of the dot.
+    pub dot: Option<(u8, u8)>,
+    pub score: Score,
+    /// the winner of the round, while shown.
+    pub winner: Option<Players>,
+}
+
+impl Snapshot {
+    pub fn of(m: &Match) -> Self {
+        Self {
+            state: m.game().into(),
+            dot: m.game().dot().map(|dot| *dot.px()),
+            score: m.score(),
+            winner: m.game().winner(),
+        }
+    }
+
+    pub fn encode(&self) -> [u8; PACKET_LEN] {
+        let (row, col) = self.dot.unwrap_or((NO_DOT, NO_DOT));
+        let winner = match self.winner {
+            None => 0,
+            Some(player) => player as u8 + 1,
+        };
+        [
+            MAGIC,
+            VERSION,
+            self.state as u8,
+            row,
+            col,
+            self.score.a,
+            self.score.b,
+            winner,
+        ]
+    }
+
+    pub fn decode(packet: &[u8]) -> Result<Self, SnapshotError> {
+        let [state, row, col, a, b, winner] = match packet {
+            [MAGIC, VERSION, fields @ ..] => fields.try_into().map_err(|_| SnapshotError::Field)?,
+            [MAGIC, version, ..] => return Err(SnapshotError::Version(*version)),
+            _ => return Err(SnapshotError::Magic),
+        };
+        Ok(Self {
+            state: State::from_u8(state).ok_or(SnapshotError::Field)?,
+            dot: match (row, col) {
+                (NO_DOT, NO_DOT) => None,
+                (0..=4, 0..=4) => Some((row, col)),
+                _ => return Err(SnapshotError::Field),
+            },
+            score: Score { a, b },
+            winner: match winner {
+                0 => None,
+                1 => Some(Players::A),
+                2 => Some(Players::B),
+                _ => return Err(SnapshotError::Field),
+            },
+        })
+    }
+
+    /// the dot, or the letter of the round winner and the wins as bars
+    /// from the bottom of the side cols.
+    pub fn draw(&self, frame: &mut Frame) {
+        if let Some(dot) = &self.dot {
+            frame.set(dot);
+        }
+        if let Some(winner) = self.winner {
+            draw_glyph(winner.letter(), WINNER_COL, frame);
+            for player in [Players::A, Players::B] {
+                let wins = self.score.wins(player).min(5);
+                frame.set_col(SCORE_COLS[player as usize], 0b11111 << (5 - wins) & 0b11111);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_broadcast {
+    use super::*;
+    use crate::{fake::*, Settings, TickClock};
+
+    #[test]
+    fn test_packets() {
+        let snapshot = Snapshot {
+            state: State::Playing,
+            dot: Some((1, 3)),
+            score: Score { a: 2, b: 1 },
+            winner: None,
+        };
+        let packet = snapshot.encode();
+        assert_eq!(packet, [b'S', VERSION, 2, 1, 3, 2, 1, 0]);
+        assert_eq!(Snapshot::decode(&packet), Ok(snapshot));
+        let result = Snapshot {
+            state: State::Result,
+            dot: None,
+            winner: Some(Players::B),
+            ..snapshot
+        };
+        assert_eq!(Snapshot::decode(&result.encode()), Ok(result));
+
+        assert_eq!(Snapshot::decode(&packet[..7]), Err(SnapshotError::Field));
+        assert_eq!(Snapshot::decode(&[1, 0, 0]), Err(SnapshotError::Magic));
+        assert_eq!(
+            Snapshot::decode(&[b'S', 2, 0]),
+            Err(SnapshotError::Version(2))
+        );
+        for (index, value) in [(2, 7), (3, 5), (7, 3)] {
+            let mut bad = packet;
+            bad[index] = value;
+            assert_eq!(Snapshot::decode(&bad), Err(SnapshotError::Field));
+        }
+    }
+
+    #[test]
+    fn test_mirror() {
+        let mut m = Match::new(Settings::new());
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        let snapshot = Snapshot::of(&m);
+        assert_eq!(snapshot.state, State::Idle);
+        assert!(snapshot.dot.is_some());
+        // play until the dot of A reaches the center
+        device.buttons.press_a(true);
+        while m.game().winner().is_none() {
+            if matches!(m.game(), Game::Playing { .. }) {
+                device.buttons.press_a(false);
+            }
+            m.poll(&mut tick, &mut device);
+            let snapshot = Snapshot::decode(&Snapshot::of(&m).encode()).unwrap();
+            if snapshot.state == State::Playing {
+                let mut frame = Frame::new();
+                snapshot.draw(&mut frame);
+                assert!(frame.is_lit(&snapshot.dot.unwrap()));
+            }
+        }
+        let snapshot = Snapshot::of(&m);
+        assert_eq!(snapshot.winner, Some(Players::A));
+        assert_eq!(snapshot.score, Score { a: 1, b: 0 });
+        let mut frame = Frame::new();
+        snapshot.draw(&mut frame);
+        // the bar of A
+        assert!(frame.is_lit(&(4, 0)) && !frame.is_lit(&(3, 0)));
+    }
+}
@@ -0,0 +1,421 @@
+use crate::{
+    ButtonEdge, ButtonInput, Chord, Debouncer, Duration, Gesture, GestureDetector, InputEvent,
+    Instant, LogoAction, Players, DEBOUNCE,
+};
+
+const LAST_BUTTON_MASK: u8 = 0b0000_0001;
+const BOTH_AB_MASK: u8 = 0b0000_0010;
+/// most buttons of a team, the one on the board and external ones.
+pub const TEAM_SIZE: usize = 4;
+
+/// An input taken by the latch, as recorded for replays.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LatchedInput {
+    /// a button is pressed, `other_held` if a button of the other team
+    /// is held down.
+    Press { player: Players, other_held: bool },
+    /// a gesture other than a single tap.
+    Gesture(Gesture),
+}
+
+/// Button presses latched by the button interrupt.
+///
+/// each button is debounced on its own, and the buttons of a team are
+/// merged: each press counts, and the team is held while any is.
+#[derive(Default)]
+pub struct ButtonLatch {
+    state: u8,
+    /// presses of A and B.
+    presses: (u8, u8),
+    /// debounced buttons of the teams of A and B, kept across `reset`.
+    buttons: [[Debouncer; TEAM_SIZE]; 2],
+    gestures: GestureDetector,
+    /// the last gesture other than a single tap.
+    gesture: Option<Gesture>,
+    /// what a touch of the logo does.
+    logo: LogoAction,
+}
+
+impl ButtonLatch {
+    pub const fn new() -> Self {
+        Self::with_debounce(DEBOUNCE)
+    }
+
+    /// ignore bounces of the buttons within `window`.
+    pub const fn with_debounce(window: Duration) -> Self {
+        Self {
+            state: 0,
+            presses: (0, 0),
+            buttons: [[Debouncer::new(window); TEAM_SIZE]; 2],
+            gestures: GestureDetector::new(),
+            gesture: None,
+            logo: LogoAction::Confirm,
+        }
+    }
+
+    #[inline]
+    pub fn set_debounce(&mut self, window: Duration) {
+        self.buttons
+            .iter_mut()
+            .flatten()
+            .for_each(|b| b.set_window(window));
+    }
+
+    /// bind a touch of the logo to `action`.
+    #[inline]
+    pub fn bind_logo(&mut self, action: LogoAction) {
+        self.logo = action;
+    }
+
+    /// debounced state of the board button of `player`.
+    #[inline]
+    pub fn button(&self, player: Players) -> &Debouncer {
+        &self.buttons[player as usize][0]
+    }
+
+    /// a button of the team of `player` is held down.
+    #[inline]
+    pub fn team_held(&self, player: Players) -> bool {
+        self.team_pressed(player) > 0
+    }
+
+    #[inline]
+    fn team_pressed(&self, player: Players) -> usize {
+        self.buttons[player as usize]
+            .iter()
+            .filter(|b| b.is_pressed())
+            .count()
+    }
+
+    /// apply a button event, in the order they happened.
+    pub fn handle_event(&mut self, event: &InputEvent) -> Option<LatchedInput> {
+        let (player, member, down, at) = match *event {
+            InputEvent::ButtonDown { player, member, at } => (player, member, true, at),
+            InputEvent::ButtonUp { player, member, at } => (player, member, false, at),
+            InputEvent::Logo { touched, .. } => {
+                return touched.then(|| self.gesture_input(self.logo.gesture()))?
+            }
+            InputEvent::TrackFinished(_) => return None,
+        };
+        let edge = self.buttons[player as usize]
+            .get_mut(member as usize)?
+            .edge(down, at);
+        self.apply(player, edge, at)
+    }
+
+    /// settle the buttons whose bounces have ended, and detect long
+    /// presses, call each tick after the events, until it returns `None`.
+    pub fn poll(&mut self, now: Instant) -> Option<LatchedInput> {
+        let press = [Players::A, Players::B]
+            .into_iter()
+            .flat_map(|player| (0..TEAM_SIZE).map(move |member| (player, member)))
+            .find_map(|(player, member)| {
+                let edge = self.buttons[player as usize][member].poll(now);
+                self.apply(player, edge, now)
+            });
+        if press.is_some() {
+            return press;
+        }
+        let gesture = self.gestures.poll(now)?;
+        self.gesture_input(gesture)
+    }
+
+    /// a debounced edge, counted as a press without the chord of A and B,
+    /// which is only pressed once released as a tap.
+    ///
+    /// the gestures see the team pressed by its first button down, and
+    /// released by its last button up.
+    fn apply(
+        &mut self,
+        player: Players,
+        edge: Option<ButtonEdge>,
+        at: Instant,
+    ) -> Option<LatchedInput> {
+        let down = edge? == ButtonEdge::Pressed;
+        let pressed = self.team_pressed(player);
+        if !down {
+            if pressed > 0 {
+                return None;
+            }
+            let gesture = self.gestures.edge(player, false, at)?;
+            return self.gesture_input(gesture);
+        }
+        if pressed == 1 {
+            self.gestures.edge(player, true, at);
+        }
+        let other_held = self.team_held(player.opponent());
+        match player {
+            Players::A => self.press_a(false),
+            Players::B => self.press_b(false),
+        }
+        Some(LatchedInput::Press { player, other_held })
+    }
+
+    #[inline]
+    fn gesture_input(&mut self, gesture: Gesture) -> Option<LatchedInput> {
+        self.set_gesture(gesture)
+            .then_some(LatchedInput::Gesture(gesture))
+    }
+
+    /// a gesture is made, e.g. from the keyboard.
+    ///
+    /// single taps are not kept, and a tap of A and B together,
+    /// a double tap, or the release of their hold, presses both.
+    ///
+    /// returns true if kept.
+    pub fn set_gesture(&mut self, gesture: Gesture) -> bool {
+        match gesture {
+            Gesture::Tap(Chord::A | Chord::B) => return false,
+            Gesture::Tap(Chord::AB)
+            | Gesture::DoubleTap(Chord::AB)
+            | Gesture::Release(Chord::AB) => self.set_both_pressed(),
+            _ => (),
+        }
+        self.gesture = Some(gesture);
+        true
+    }
+
+    /// the gesture to be taken by `take_gesture`.
+    #[inline]
+    pub fn gesture(&self) -> Option<Gesture> {
+        self.gesture
+    }
+
+    /// A is held down or released.
+    #[inline]
+    pub fn hold_a(&mut self, held: bool) {
+        self.buttons[0][0].hold(held);
+    }
+
+    /// button A pressed, `b_held` if B is currently held down.
+    #[inline]
+    pub fn press_a(&mut self, b_held: bool) {
+        self.presses.0 = self.presses.0.saturating_add(1);
+        self.set_last_a();
+        if b_held {
+            self.set_both_pressed();
+        }
+    }
+
+    /// button B pressed, `a_held` if A is currently held down.
+    #[inline]
+    pub fn press_b(&mut self, a_held: bool) {
+        self.presses.1 = self.presses.1.saturating_add(1);
+        self.set_last_b();
+        if a_held {
+            self.set_both_pressed();
+        }
+    }
+
+    #[inline]
+    fn set_last_b(&mut self) {
+        self.state &= !LAST_BUTTON_MASK;
+    }
+
+    #[inline]
+    fn set_both_pressed(&mut self) {
+        self.state |= BOTH_AB_MASK;
+    }
+}
+
+impl ButtonInput for ButtonLatch {
+    #[inline]
+    fn both_pressed(&self) -> bool {
+        self.state & BOTH_AB_MASK != 0
+    }
+
+    #[inline]
+    fn last_a(&self) -> bool {
+        self.state & LAST_BUTTON_MASK != 0
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.state = 0;
+        self.presses = (0, 0);
+        self.gesture = None;
+    }
+
+    #[inline]
+    fn set_last_a(&mut self) {
+        self.state |= LAST_BUTTON_MASK;
+    }
+
+    #[inline]
+    fn take_presses(&mut self) -> (u8, u8) {
+        core::mem::take(&mut self.presses)
+    }
+
+    #[inline]
+    fn a_held(&self) -> bool {
+        self.team_held(Players::A)
+    }
+
+    #[inline]
+    fn take_gesture(&mut self) -> Option<Gesture> {
+        self.gesture.take()
+    }
+}
+
+#[cfg(test)]
+mod test_buttons {
+    use super::*;
+    use crate::{EventQueue, Track};
+
+    fn down(player: Players, tick: u32) -> InputEvent {
+        member_down(player, 0, tick)
+    }
+
+    fn up(player: Players, tick: u32) -> InputEvent {
+        member_up(player, 0, tick)
+    }
+
+    fn member_down(player: Players, member: u8, tick: u32) -> InputEvent {
+        InputEvent::ButtonDown {
+            player,
+            member,
+            at: Instant::from_ticks(tick),
+        }
+    }
+
+    fn member_up(player: Players, member: u8, tick: u32) -> InputEvent {
+        InputEvent::ButtonUp {
+            player,
+            member,
+            at: Instant::from_ticks(tick),
+        }
+    }
+
+    fn press(player: Players, other_held: bool) -> LatchedInput {
+        LatchedInput::Press { player, other_held }
+    }
+
+    #[test]
+    fn test_handle_events() {
+        let queue = EventQueue::<InputEvent, 8>::new();
+        for event in [
+            down(Players::A, 0),
+            // bounce
+            up(Players::A, 1),
+            down(Players::A, 2),
+            down(Players::B, 3),
+            up(Players::A, 10),
+            InputEvent::TrackFinished(Track::DiHi),
+            down(Players::A, 20),
+        ] {
+            queue.push(event);
+        }
+        let mut latch = ButtonLatch::new();
+        let presses: Vec<_> = core::iter::from_fn(|| queue.pop())
+            .filter_map(|event| latch.handle_event(&event))
+            .collect();
+        assert_eq!(
+            presses,
+            [
+                press(Players::A, false),
+                press(Players::B, true),
+                press(Players::A, true)
+            ]
+        );
+        assert_eq!(latch.take_presses(), (2, 1));
+        assert!(latch.last_a() && latch.a_held());
+        assert_eq!(
+            latch.button(Players::B).held_for(Instant::from_ticks(23)),
+            Some(Duration::from_ticks(20))
+        );
+        // both are pressed once the chord is released
+        assert!(!latch.both_pressed());
+        assert_eq!(latch.handle_event(&up(Players::A, 30)), None);
+        assert_eq!(
+            latch.handle_event(&up(Players::B, 31)),
+            Some(LatchedInput::Gesture(Gesture::Tap(Chord::AB)))
+        );
+        assert!(latch.both_pressed());
+        latch.handle_event(&down(Players::A, 40));
+        assert_eq!(latch.poll(Instant::from_ticks(40 + 255)), None);
+        assert_eq!(
+            latch.poll(Instant::from_ticks(40 + 256)),
+            Some(LatchedInput::Gesture(Gesture::LongPress(Chord::A)))
+        );
+        assert_eq!(latch.take_gesture(), Some(Gesture::LongPress(Chord::A)));
+    }
+
+    #[test]
+    fn test_logo() {
+        let mut latch = ButtonLatch::new();
+        let touch = |touched| InputEvent::Logo {
+            touched,
+            at: Instant::from_ticks(0),
+        };
+        assert_eq!(
+            latch.handle_event(&touch(true)),
+            Some(LatchedInput::Gesture(Gesture::Tap(Chord::AB)))
+        );
+        assert!(latch.both_pressed());
+        assert_eq!(latch.handle_event(&touch(false)), None);
+        latch.bind_logo(LogoAction::Restart);
+        latch.handle_event(&touch(true));
+        assert_eq!(latch.take_gesture(), Some(crate::RESTART));
+    }
+
+    #[test]
+    fn test_poll_settles() {
+        let mut latch = ButtonLatch::with_debounce(Duration::from_ticks(4));
+        assert_eq!(
+            latch.handle_event(&down(Players::B, 0)),
+            Some(press(Players::B, false))
+        );
+        assert_eq!(latch.handle_event(&up(Players::B, 1)), None);
+        // A pressed while B bounces, B released once settled
+        assert_eq!(
+            latch.handle_event(&down(Players::A, 2)),
+            Some(press(Players::A, true))
+        );
+        assert_eq!(latch.poll(Instant::from_ticks(3)), None);
+        assert_eq!(latch.poll(Instant::from_ticks(4)), None);
+        assert!(!latch.button(Players::B).is_pressed());
+        // a press within the window of the release
+        latch.handle_event(&down(Players::B, 5));
+        assert_eq!(latch.poll(Instant::from_ticks(7)), None);
+        assert_eq!(
+            latch.poll(Instant::from_ticks(8)),
+            Some(press(Players::B, true))
+        );
+        assert_eq!(latch.take_presses(), (1, 2));
+    }
+
+    #[test]
+    fn test_teams() {
+        let mut latch = ButtonLatch::new();
+        assert_eq!(
+            latch.handle_event(&member_down(Players::A, 1, 0)),
+            Some(press(Players::A, false))
+        );
+        assert_eq!(
+            latch.handle_event(&member_down(Players::B, 2, 1)),
+            Some(press(Players::B, true))
+        );
+        // the other member of A, while the first one is still held
+        assert_eq!(
+            latch.handle_event(&down(Players::A, 8)),
+            Some(press(Players::A, true))
+        );
+        assert_eq!(latch.take_presses(), (2, 1));
+        // the team is held until its last button is released
+        latch.handle_event(&member_up(Players::A, 1, 20));
+        assert!(latch.a_held());
+        latch.handle_event(&up(Players::A, 30));
+        assert!(!latch.a_held());
+        assert!(latch.team_held(Players::B));
+        assert_eq!(
+            latch.handle_event(&member_up(Players::B, 2, 40)),
+            Some(LatchedInput::Gesture(Gesture::Tap(Chord::AB)))
+        );
+        // no such member
+        let member = TEAM_SIZE as u8;
+        assert_eq!(
+            latch.handle_event(&member_down(Players::A, member, 50)),
+            None
+        );
+    }
+}
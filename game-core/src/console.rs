@@ -0,0 +1,454 @@
+//! Commands of the serial console, to tune the game of a booth without
+//! reflashing the board.
+//!
+//! A command is a line of words, ended by `\r` or `\n`:
+//!
+//! - `get`: the settings, and the volume.
+//! - `set <option> <value>`: change a setting, see [`Setting`], or the
+//!   volume, `0` to [`MAX_VOLUME`].
+//! - `stats`: the lifetime stats.
+//! - `log`: the match log of the last round, in hex.
+//! - `play <track>`: play `di-lo`, `di-hi`, `peppa`, `fanfare` or `buzz`.
+//! - `reset`: back to the idle animation, clearing the score.
+//! - `telemetry on|off`: mute the telemetry frames while typing.
+//! - `help`: the commands.
+//!
+//! The settings are printed as the `set` commands restoring them.
+use crate::{store::Stats, BestOf, Difficulty, FalseStart, Rule, Settings, Speed, Track};
+use core::fmt::{self, Write};
+
+/// bytes of the longest line.
+pub const MAX_LINE: usize = 32;
+/// the loudest volume, the notes as sampled.
+pub const MAX_VOLUME: u8 = 10;
+/// bytes of the match log on a line.
+const LOG_LINE: usize = 32;
+
+pub const HELP: &str = "get
+set countdown 1-5
+set speed slow|normal|fast
+set rule last-press|tap-rate
+set best-of 1|3|5|7
+set false-start off|forfeit|penalty
+set bot off|easy|medium|hard
+set sound on|off
+set volume 0-10
+stats
+log
+play di-lo|di-hi|peppa|fanfare|buzz
+reset
+telemetry on|off
+";
+
+const SPEEDS: [(&str, Speed); 3] = [
+    ("slow", Speed::Slow),
+    ("normal", Speed::Normal),
+    ("fast", Speed::Fast),
+];
+const RULES: [(&str, Rule); 2] = [("last-press", Rule::LastPress), ("tap-rate", Rule::TapRate)];
+const FALSE_STARTS: [(&str, FalseStart); 3] = [
+    ("off", FalseStart::Off),
+    ("forfeit", FalseStart::Forfeit),
+    ("penalty", FalseStart::Penalty),
+];
+const BOTS: [(&str, Option<Difficulty>); 4] = [
+    ("off", None),
+    ("easy", Some(Difficulty::Easy)),
+    ("medium", Some(Difficulty::Medium)),
+    ("hard", Some(Difficulty::Hard)),
+];
+const SWITCHES: [(&str, bool); 2] = [("off", false), ("on", true)];
+const TRACKS: [(&str, Track); 5] = [
+    ("di-lo", Track::DiLo),
+    ("di-hi", Track::DiHi),
+    ("peppa", Track::Peppa),
+    ("fanfare", Track::Fanfare),
+    ("buzz", Track::Buzz),
+];
+
+/// A setting of the game, with its new value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Setting {
+    /// seconds, 1 to [`MAX_COUNTDOWN`](crate::MAX_COUNTDOWN).
+    Countdown(u8),
+    /// the step rate of the dot.
+    Speed(Speed),
+    Rule(Rule),
+    BestOf(BestOf),
+    FalseStart(FalseStart),
+    Bot(Option<Difficulty>),
+    Sound(bool),
+}
+
+impl Setting {
+    pub fn apply(self, settings: &mut Settings) {
+        match self {
+            Setting::Countdown(countdown) => settings.countdown = countdown,
+            Setting::Speed(speed) => settings.speed = speed,
+            Setting::Rule(rule) => settings.rule = rule,
+            Setting::BestOf(best_of) => settings.best_of = best_of,
+            Setting::FalseStart(false_start) => settings.false_start = false_start,
+            Setting::Bot(bot) => settings.bot = bot,
+            Setting::Sound(sound) => settings.sound = sound,
+        }
+    }
+}
+
+/// A line of the console.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    Get,
+    Set(Setting),
+    /// `set volume`, not saved with the settings.
+    Volume(u8),
+    Stats,
+    /// the match log of the last round.
+    Log,
+    Play(Track),
+    Reset,
+    Telemetry(bool),
+    Help,
+}
+
+/// A line is not a command.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    Command,
+    Option,
+    /// a missing or invalid value of the option or track.
+    Value,
+    /// words after the command.
+    Trailing,
+    /// longer than [`MAX_LINE`], or not text.
+    Line,
+}
+
+impl ParseError {
+    pub fn message(self) -> &'static str {
+        match self {
+            ParseError::Command => "unknown command, try help",
+            ParseError::Option => "unknown option, try help",
+            ParseError::Value => "bad value, try help",
+            ParseError::Trailing => "too many words",
+            ParseError::Line => "line too long, or not text",
+        }
+    }
+}
+
+/// parse a line, without its line end.
+pub fn parse(line: &str) -> Result<Command, ParseError> {
+    let mut words = line.split_ascii_whitespace();
+    let command = match words.next().ok_or(ParseError::Command)? {
+        "get" => Command::Get,
+        "set" => {
+            let option = words.next().ok_or(ParseError::Option)?;
+            let value = words.next().ok_or(ParseError::Value)?;
+            parse_set(option, value)?
+        }
+        "stats" => Command::Stats,
+        "log" => Command::Log,
+        "play" => Command::Play(lookup(&TRACKS, words.next())?),
+        "reset" => Command::Reset,
+        "telemetry" => Command::Telemetry(lookup(&SWITCHES, words.next())?),
+        "help" => Command::Help,
+        _ => return Err(ParseError::Command),
+    };
+    match words.next() {
+        Some(_) => Err(ParseError::Trailing),
+        None => Ok(command),
+    }
+}
+
+fn parse_set(option: &str, value: &str) -> Result<Command, ParseError> {
+    let value = Some(value);
+    let setting = match option {
+        "countdown" => Setting::Countdown(number(value, 1, crate::MAX_COUNTDOWN)?),
+        "speed" => Setting::Speed(lookup(&SPEEDS, value)?),
+        "rule" => Setting::Rule(lookup(&RULES, value)?),
+        "best-of" => {
+            let best_of = number(value, 1, 7)?;
+            Setting::BestOf(BestOf::from_u8(best_of).ok_or(ParseError::Value)?)
+        }
+        "false-start" => Setting::FalseStart(lookup(&FALSE_STARTS, value)?),
+        "bot" => Setting::Bot(lookup(&BOTS, value)?),
+        "sound" => Setting::Sound(lookup(&SWITCHES, value)?),
+        "volume" => return Ok(Command::Volume(number(value, 0, MAX_VOLUME)?)),
+        _ => return Err(ParseError::Option),
+    };
+    Ok(Command::Set(setting))
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], word: Option<&str>) -> Result<T, ParseError> {
+    let word = word.ok_or(ParseError::Value)?;
+    table
+        .iter()
+        .find(|(name, _)| *name == word)
+        .map(|(_, value)| *value)
+        .ok_or(ParseError::Value)
+}
+
+fn number(word: Option<&str>, min: u8, max: u8) -> Result<u8, ParseError> {
+    word.and_then(|word| word.parse().ok())
+        .filter(|value| (min..=max).contains(value))
+        .ok_or(ParseError::Value)
+}
+
+fn name<T: PartialEq>(table: &[(&'static str, T)], value: T) -> &'static str {
+    table
+        .iter()
+        .find(|(_, other)| *other == value)
+        .map_or("?", |(name, _)| name)
+}
+
+/// the settings and the volume, a line each.
+pub fn write_settings(settings: &Settings, volume: u8, out: &mut impl Write) -> fmt::Result {
+    writeln!(out, "countdown {}", settings.countdown)?;
+    writeln!(out, "speed {}", name(&SPEEDS, settings.speed))?;
+    writeln!(out, "rule {}", name(&RULES, settings.rule))?;
+    writeln!(out, "best-of {}", settings.best_of as u8)?;
+    writeln!(
+        out,
+        "false-start {}",
+        name(&FALSE_STARTS, settings.false_start)
+    )?;
+    writeln!(out, "bot {}", name(&BOTS, settings.bot))?;
+    writeln!(out, "sound {}", name(&SWITCHES, settings.sound))?;
+    writeln!(out, "volume {volume}")
+}
+
+pub fn write_stats(stats: &Stats, out: &mut impl Write) -> fmt::Result {
+    writeln!(out, "rounds {}", stats.rounds)?;
+    writeln!(out, "wins a {}", stats.wins_a)?;
+    writeln!(out, "wins b {}", stats.wins_b)
+}
+
+/// the match log of a round, in hex lines of 32 bytes, which
+/// `xxd -r -p` turns back into bytes.
+///
+/// `overflow` if records were dropped, the replay ending early.
+pub fn write_log(log: &[u8], overflow: bool, out: &mut impl Write) -> fmt::Result {
+    if log.is_empty() {
+        return writeln!(out, "error: no round recorded");
+    }
+    for line in log.chunks(LOG_LINE) {
+        line.iter().try_for_each(|byte| write!(out, "{byte:02x}"))?;
+        writeln!(out)?;
+    }
+    match overflow {
+        true => writeln!(out, "error: log overflowed"),
+        false => Ok(()),
+    }
+}
+
+/// Lines of the console, received a byte at a time.
+pub struct LineReader {
+    line: [u8; MAX_LINE],
+    len: usize,
+    /// the line is longer than `line`, and is dropped at its end.
+    overflow: bool,
+}
+
+impl LineReader {
+    pub const fn new() -> Self {
+        Self {
+            line: [0; MAX_LINE],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// no byte of a line received yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0 && !self.overflow
+    }
+
+    /// feed a received byte, returns the command once its line ends.
+    /// Blank lines are skipped, and backspace deletes a byte, for
+    /// people typing in a terminal.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Command, ParseError>> {
+        match byte {
+            b'\r' | b'\n' => {
+                let line = &self.line[..self.len];
+                let overflow = core::mem::take(&mut self.overflow);
+                self.len = 0;
+                if overflow {
+                    return Some(Err(ParseError::Line));
+                }
+                let line = core::str::from_utf8(line).map_err(|_| ParseError::Line);
+                match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    line => Some(line.and_then(parse)),
+                }
+            }
+            0x08 | 0x7f => {
+                self.len = self.len.saturating_sub(1);
+                None
+            }
+            byte if self.len < MAX_LINE => {
+                self.line[self.len] = byte.to_ascii_lowercase();
+                self.len += 1;
+                None
+            }
+            _ => {
+                self.overflow = true;
+                None
+            }
+        }
+    }
+}
+
+impl Default for LineReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_console {
+    use super::*;
+    use crate::{replay::Recorder, Players};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("get"), Ok(Command::Get));
+        assert_eq!(
+            parse("set countdown 5"),
+            Ok(Command::Set(Setting::Countdown(5)))
+        );
+        assert_eq!(
+            parse("  set   speed fast "),
+            Ok(Command::Set(Setting::Speed(Speed::Fast)))
+        );
+        assert_eq!(
+            parse("set rule tap-rate"),
+            Ok(Command::Set(Setting::Rule(Rule::TapRate)))
+        );
+        assert_eq!(
+            parse("set best-of 7"),
+            Ok(Command::Set(Setting::BestOf(BestOf::Seven)))
+        );
+        assert_eq!(parse("set bot off"), Ok(Command::Set(Setting::Bot(None))));
+        assert_eq!(parse("set volume 0"), Ok(Command::Volume(0)));
+        assert_eq!(parse("play fanfare"), Ok(Command::Play(Track::Fanfare)));
+        assert_eq!(parse("telemetry off"), Ok(Command::Telemetry(false)));
+        assert_eq!(parse("reset"), Ok(Command::Reset));
+        assert_eq!(parse("log"), Ok(Command::Log));
+
+        assert_eq!(parse("jump"), Err(ParseError::Command));
+        assert_eq!(parse("set colour red"), Err(ParseError::Option));
+        assert_eq!(parse("set speed"), Err(ParseError::Value));
+        assert_eq!(parse("set countdown 6"), Err(ParseError::Value));
+        assert_eq!(parse("set countdown 0"), Err(ParseError::Value));
+        assert_eq!(parse("set best-of 2"), Err(ParseError::Value));
+        assert_eq!(parse("set volume 11"), Err(ParseError::Value));
+        assert_eq!(parse("set volume -1"), Err(ParseError::Value));
+        assert_eq!(parse("play"), Err(ParseError::Value));
+        assert_eq!(parse("reset now"), Err(ParseError::Trailing));
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings = Settings {
+            rule: Rule::TapRate,
+            best_of: BestOf::Five,
+            false_start: FalseStart::Penalty,
+            bot: Some(Difficulty::Medium),
+            speed: Speed::Slow,
+            countdown: 2,
+            sound: false,
+        };
+        let mut out = String::new();
+        write_settings(&settings, 7, &mut out).unwrap();
+        assert_eq!(
+            out,
+            "countdown 2\nspeed slow\nrule tap-rate\nbest-of 5\n\
+             false-start penalty\nbot medium\nsound off\nvolume 7\n"
+        );
+        // the printed settings restore them
+        let mut restored = Settings::new();
+        for line in out.lines() {
+            match parse(&format!("set {line}")) {
+                Ok(Command::Set(setting)) => setting.apply(&mut restored),
+                Ok(Command::Volume(volume)) => assert_eq!(volume, 7),
+                other => panic!("{line}: {other:?}"),
+            }
+        }
+        assert_eq!(restored, settings);
+
+        let mut out = String::new();
+        let stats = Stats {
+            rounds: 3,
+            wins_a: 2,
+            wins_b: 1,
+        };
+        write_stats(&stats, &mut out).unwrap();
+        assert_eq!(out, "rounds 3\nwins a 2\nwins b 1\n");
+        // every command of the help parses
+        for line in HELP.lines() {
+            let line = line.replace("1-5", "1").replace("0-10", "0");
+            let line = line.split('|').next().unwrap();
+            assert!(parse(line).is_ok(), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_log() {
+        let mut recorder = Recorder::<64>::new();
+        let mut out = String::new();
+        write_log(recorder.log(), false, &mut out).unwrap();
+        assert_eq!(out, "error: no round recorded\n");
+
+        recorder.start(&Settings::new());
+        for _ in 0..30 {
+            recorder.tick();
+            recorder.press(Players::A, false);
+        }
+        let mut out = String::new();
+        write_log(recorder.log(), recorder.overflowed(), &mut out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("544f57") && lines[0].len() == 64);
+        // the hex gives back the log
+        let bytes: Vec<_> = lines
+            .concat()
+            .as_bytes()
+            .chunks(2)
+            .map(|hex| u8::from_str_radix(core::str::from_utf8(hex).unwrap(), 16).unwrap())
+            .collect();
+        assert_eq!(bytes, recorder.log());
+
+        let mut out = String::new();
+        write_log(&[0xab], true, &mut out).unwrap();
+        assert_eq!(out, "ab\nerror: log overflowed\n");
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut reader = LineReader::new();
+        let mut commands = vec![];
+        let bytes = b"\r\n  \nSET Speed fast\r\nplax\x7fy buzz\n\
+                      set countdown 12345678901234567890123456789\n\
+                      get\xff\n";
+        for byte in bytes {
+            commands.extend(reader.push(*byte));
+        }
+        assert_eq!(
+            commands,
+            [
+                Ok(Command::Set(Setting::Speed(Speed::Fast))),
+                Ok(Command::Play(Track::Buzz)),
+                Err(ParseError::Line),
+                Err(ParseError::Line),
+            ]
+        );
+        // the reader recovers after an overflow
+        for byte in b"help\n" {
+            commands.extend(reader.push(*byte));
+        }
+        assert_eq!(commands.last(), Some(&Ok(Command::Help)));
+        assert!(reader.is_empty());
+        reader.push(b'g');
+        assert!(!reader.is_empty());
+    }
+}
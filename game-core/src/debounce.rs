@@ -0,0 +1,201 @@
+//! Debouncing of the button edges, timed with the RTC counter.
+use crate::{Duration, Instant};
+
+/// edges closer than this to an accepted change are contact bounce.
+pub const DEBOUNCE: Duration = Duration::from_ms(20);
+
+/// A debounced change of a button.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ButtonEdge {
+    Pressed,
+    /// released after being held for `held`.
+    Released {
+        held: Duration,
+    },
+}
+
+/// Debounced state of one button.
+///
+/// the first edge of a change is accepted right away, and the edges
+/// following it within the window are ignored. A level still differing
+/// once the window has passed is accepted by `poll`.
+#[derive(Clone, Copy, Debug)]
+pub struct Debouncer {
+    window: Duration,
+    /// debounced state.
+    pressed: bool,
+    /// level of the last edge.
+    level: bool,
+    /// time of the last accepted change.
+    changed: Option<Instant>,
+}
+
+impl Debouncer {
+    pub const fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pressed: false,
+            level: false,
+            changed: None,
+        }
+    }
+
+    #[inline]
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    #[inline]
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    /// the button went down (`down`) or up at `at`.
+    pub fn edge(&mut self, down: bool, at: Instant) -> Option<ButtonEdge> {
+        self.level = down;
+        self.poll(at)
+    }
+
+    /// accept the level of the last edge, once past the window.
+    /// call each tick, after the edges.
+    pub fn poll(&mut self, now: Instant) -> Option<ButtonEdge> {
+        if self.level == self.pressed
+            || self
+                .changed
+                .is_some_and(|changed| now.since(changed) < self.window)
+        {
+            return None;
+        }
+        let held = self.held_for(now);
+        self.pressed = self.level;
+        self.changed = Some(now);
+        Some(match held {
+            None => ButtonEdge::Pressed,
+            Some(held) => ButtonEdge::Released { held },
+        })
+    }
+
+    #[inline]
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// how long the button has been pressed, if it is.
+    pub fn held_for(&self, now: Instant) -> Option<Duration> {
+        match self.changed {
+            Some(changed) if self.pressed => Some(now.since(changed)),
+            None if self.pressed => Some(Duration::default()),
+            _ => None,
+        }
+    }
+
+    /// press or release without an edge, e.g. from the keyboard.
+    #[inline]
+    pub fn hold(&mut self, pressed: bool) {
+        self.pressed = pressed;
+        self.level = pressed;
+        self.changed = None;
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Self::new(DEBOUNCE)
+    }
+}
+
+#[cfg(test)]
+mod test_debounce {
+    use super::*;
+
+    /// feed `(tick, down)` edges, polling every tick up to `end`.
+    fn run(debouncer: &mut Debouncer, edges: &[(u32, bool)], end: u32) -> Vec<(u32, ButtonEdge)> {
+        let mut changes = Vec::new();
+        let mut edges = edges.iter().peekable();
+        for tick in 0..end {
+            let now = Instant::from_ticks(tick);
+            while let Some((_, down)) = edges.next_if(|(at, _)| *at == tick) {
+                changes.extend(debouncer.edge(*down, now).map(|edge| (tick, edge)));
+            }
+            changes.extend(debouncer.poll(now).map(|edge| (tick, edge)));
+        }
+        changes
+    }
+
+    #[test]
+    fn test_bouncing_press() {
+        let mut debouncer = Debouncer::new(Duration::from_ticks(5));
+        // bounces on press and release
+        let edges = [
+            (10, true),
+            (10, false),
+            (11, true),
+            (12, false),
+            (12, true),
+            (40, false),
+            (41, true),
+            (43, false),
+        ];
+        assert_eq!(
+            run(&mut debouncer, &edges, 60),
+            [
+                (10, ButtonEdge::Pressed),
+                (
+                    40,
+                    ButtonEdge::Released {
+                        held: Duration::from_ticks(30)
+                    }
+                ),
+            ]
+        );
+        assert!(!debouncer.is_pressed());
+    }
+
+    #[test]
+    fn test_settles_after_window() {
+        let mut debouncer = Debouncer::new(Duration::from_ticks(5));
+        // released, then pressed again within the window
+        let edges = [(0, true), (2, false), (3, true), (20, false)];
+        assert_eq!(
+            run(&mut debouncer, &edges, 21),
+            [
+                (0, ButtonEdge::Pressed),
+                (
+                    20,
+                    ButtonEdge::Released {
+                        held: Duration::from_ticks(20)
+                    }
+                ),
+            ]
+        );
+        // a tap shorter than the window ends once it has passed
+        let edges = [(30, true), (31, false)];
+        let changes = run(&mut debouncer, &edges, 40);
+        assert_eq!(changes[0], (30, ButtonEdge::Pressed));
+        assert_eq!(
+            changes[1],
+            (
+                35,
+                ButtonEdge::Released {
+                    held: Duration::from_ticks(5)
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_window_and_held_for() {
+        let mut debouncer = Debouncer::new(Duration::default());
+        let edges = [(0, true), (1, false), (2, true)];
+        assert_eq!(run(&mut debouncer, &edges, 3).len(), 3);
+        assert_eq!(
+            debouncer.held_for(Instant::from_ticks(258)),
+            Some(Duration::from_secs(1))
+        );
+        debouncer.set_window(DEBOUNCE);
+        assert_eq!(debouncer.edge(false, Instant::from_ticks(3)), None);
+        assert!(debouncer.is_pressed());
+        debouncer.hold(false);
+        assert_eq!(debouncer.held_for(Instant::from_ticks(4)), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Fake devices for tests.
+use crate::{ButtonLatch, Device, Frame, LedMatrix, RandomSource, SoundSink, Track, SIZE};
+
+/// The last frame shown.
+#[derive(Default)]
+pub struct FakeMatrix(pub Frame);
+
+impl FakeMatrix {
+    pub fn lit(&self) -> Vec<(u8, u8)> {
+        let mut lit = Vec::new();
+        for x in 0..SIZE {
+            for y in 0..SIZE {
+                if self.0.is_lit(&(x, y)) {
+                    lit.push((x, y));
+                }
+            }
+        }
+        lit
+    }
+}
+
+impl LedMatrix for FakeMatrix {
+    fn show(&mut self, frame: &Frame) {
+        self.0 = *frame;
+    }
+}
+
+/// The tracks played, and the times stopped.
+#[derive(Default)]
+pub struct FakeSound(pub Vec<Track>, pub usize);
+
+impl SoundSink for FakeSound {
+    fn play_track(&mut self, track: Track) {
+        self.0.push(track)
+    }
+
+    fn stop(&mut self) {
+        self.1 += 1
+    }
+}
+
+pub struct FakeRng(pub u8);
+
+impl RandomSource for FakeRng {
+    fn random_u8(&mut self) -> u8 {
+        self.0
+    }
+}
+
+pub type FakeDevice = Device<ButtonLatch, FakeMatrix, FakeSound, FakeRng>;
+
+pub fn fake_device(random: u8) -> FakeDevice {
+    Device {
+        buttons: ButtonLatch::new(),
+        display: FakeMatrix::default(),
+        sound: FakeSound::default(),
+        rng: FakeRng(random),
+    }
+}
@@ -0,0 +1,117 @@
+//! A 5 rows high bitmap font, with glyphs of variable width.
+use crate::{Frame, SIZE};
+
+/// blank cols between glyphs.
+const SPACING: u8 = 1;
+
+/// cols of a glyph by columns, bit 0 is the top row.
+///
+/// lowercase letters are shown as uppercase, unknown chars as `?`.
+pub fn glyph(c: char) -> &'static [u8] {
+    match c.to_ascii_uppercase() {
+        '0' => &[0b01110, 0b10001, 0b10001, 0b01110],
+        '1' => &[0b10010, 0b11111, 0b10000],
+        '2' => &[0b11001, 0b10101, 0b10101, 0b10010],
+        '3' => &[0b01001, 0b10001, 0b10101, 0b01011],
+        '4' => &[0b00111, 0b00100, 0b11111, 0b00100],
+        '5' => &[0b10111, 0b10101, 0b10101, 0b01001],
+        '6' => &[0b01110, 0b10101, 0b10101, 0b01000],
+        '7' => &[0b00001, 0b11001, 0b00101, 0b00011],
+        '8' => &[0b01010, 0b10101, 0b10101, 0b01010],
+        '9' => &[0b00010, 0b10101, 0b10101, 0b01110],
+        'A' => &[0b11110, 0b00101, 0b00101, 0b11110],
+        'B' => &[0b11111, 0b10101, 0b10101, 0b01010],
+        'C' => &[0b01110, 0b10001, 0b10001, 0b10001],
+        'D' => &[0b11111, 0b10001, 0b10001, 0b01110],
+        'E' => &[0b11111, 0b10101, 0b10101, 0b10001],
+        'F' => &[0b11111, 0b00101, 0b00101, 0b00001],
+        'G' => &[0b01110, 0b10001, 0b10101, 0b11101],
+        'H' => &[0b11111, 0b00100, 0b00100, 0b11111],
+        'I' => &[0b10001, 0b11111, 0b10001],
+        'J' => &[0b01000, 0b10000, 0b10001, 0b01111],
+        'K' => &[0b11111, 0b00100, 0b01010, 0b10001],
+        'L' => &[0b11111, 0b10000, 0b10000, 0b10000],
+        'M' => &[0b11111, 0b00010, 0b00100, 0b00010, 0b11111],
+        'N' => &[0b11111, 0b00010, 0b00100, 0b01000, 0b11111],
+        'O' => &[0b01110, 0b10001, 0b10001, 0b01110],
+        'P' => &[0b11111, 0b00101, 0b00101, 0b00010],
+        'Q' => &[0b01110, 0b10001, 0b01001, 0b10110],
+        'R' => &[0b11111, 0b00101, 0b01101, 0b10010],
+        'S' => &[0b10010, 0b10101, 0b10101, 0b01001],
+        'T' => &[0b00001, 0b11111, 0b00001],
+        'U' => &[0b01111, 0b10000, 0b10000, 0b01111],
+        'V' => &[0b00011, 0b01100, 0b10000, 0b01100, 0b00011],
+        'W' => &[0b11111, 0b01000, 0b00100, 0b01000, 0b11111],
+        'X' => &[0b11011, 0b00100, 0b00100, 0b11011],
+        'Y' => &[0b00011, 0b11100, 0b00011],
+        'Z' => &[0b11001, 0b10101, 0b10011, 0b10001],
+        ' ' => &[0b00000, 0b00000],
+        '-' => &[0b00100, 0b00100, 0b00100],
+        '+' => &[0b00100, 0b01110, 0b00100],
+        '!' => &[0b10111],
+        '?' => &[0b00001, 0b10101, 0b00101, 0b00010],
+        ':' => &[0b01010],
+        '.' => &[0b10000],
+        _ => glyph('?'),
+    }
+}
+
+/// width of a text in cols, with spacing between glyphs.
+pub fn text_width(text: &str) -> u8 {
+    let width: u8 = text.chars().map(|c| glyph(c).len() as u8 + SPACING).sum();
+    width.saturating_sub(SPACING)
+}
+
+/// draw a glyph from `first_col`, cols outside the matrix are clipped.
+///
+/// returns the width of the glyph.
+#[inline]
+pub fn draw_glyph(c: char, first_col: i16, frame: &mut Frame) -> u8 {
+    let glyph = glyph(c);
+    for (col, col_code) in (first_col..).zip(glyph) {
+        if (0..SIZE as i16).contains(&col) {
+            frame.set_col(col as u8, *col_code);
+        }
+    }
+    glyph.len() as u8
+}
+
+/// draw a text from `first_col`, cols outside the matrix are clipped.
+pub fn draw_text(text: &str, first_col: i16, frame: &mut Frame) {
+    let mut col = first_col;
+    for c in text.chars() {
+        if col >= SIZE as i16 {
+            break;
+        }
+        col += (draw_glyph(c, col, frame) + SPACING) as i16;
+    }
+}
+
+#[cfg(test)]
+mod test_font {
+    use super::*;
+
+    #[test]
+    fn test_glyphs() {
+        for c in ('0'..='9').chain('A'..='Z').chain(" -+!?:.".chars()) {
+            let glyph = glyph(c);
+            assert!((1..=5).contains(&glyph.len()), "{c}");
+            assert!(glyph.iter().all(|col| *col < 1 << SIZE), "{c}");
+        }
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('~'), glyph('?'));
+        assert_eq!(text_width("A WINS"), 4 + 2 + 5 + 3 + 5 + 4 + 5);
+        assert_eq!(text_width(""), 0);
+    }
+
+    #[test]
+    fn test_draw_text_clipped() {
+        let mut frame = Frame::new();
+        // the right col of A, spacing, and the left cols of B
+        draw_text("AB", -3, &mut frame);
+        let mut expected = Frame::new();
+        expected.set_col(0, glyph('A')[3]);
+        expected.set_cols(2, &glyph('B')[..3]);
+        assert_eq!(frame, expected);
+    }
+}
@@ -0,0 +1,111 @@
+//! A frame of the 5x5 LED matrix.
+
+/// rows and cols of the matrix.
+pub const SIZE: u8 = 5;
+/// brightness of a fully lit pixel, 0 is off.
+pub const MAX_BRIGHTNESS: u8 = 9;
+
+/// Brightness of each pixel of the matrix, by rows.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Frame {
+    pixels: [[u8; SIZE as usize]; SIZE as usize],
+}
+
+impl Frame {
+    /// all pixels off.
+    pub const fn new() -> Self {
+        Self {
+            pixels: [[0; SIZE as usize]; SIZE as usize],
+        }
+    }
+
+    /// fully turn on the pixel at (row, col).
+    #[inline]
+    pub fn set(&mut self, px: &(u8, u8)) {
+        self.set_brightness(px, MAX_BRIGHTNESS);
+    }
+
+    /// set the brightness of the pixel at (row, col), up to [`MAX_BRIGHTNESS`].
+    #[inline]
+    pub fn set_brightness(&mut self, &(row, col): &(u8, u8), brightness: u8) {
+        self.pixels[row as usize][col as usize] = brightness.min(MAX_BRIGHTNESS);
+    }
+
+    #[inline]
+    pub fn brightness(&self, &(row, col): &(u8, u8)) -> u8 {
+        self.pixels[row as usize][col as usize]
+    }
+
+    #[inline]
+    pub fn is_lit(&self, px: &(u8, u8)) -> bool {
+        self.brightness(px) > 0
+    }
+
+    /// fully turn on pixels of a column, `col_code` bits select the rows.
+    #[inline]
+    pub fn set_col(&mut self, col: u8, col_code: u8) {
+        for (i, row) in self.pixels.iter_mut().enumerate() {
+            if col_code & (1 << i) != 0 {
+                row[col as usize] = MAX_BRIGHTNESS;
+            }
+        }
+    }
+
+    /// draw cols from `first_col`, one col code per col.
+    #[inline]
+    pub fn set_cols(&mut self, first_col: u8, col_codes: &[u8]) {
+        for (col, col_code) in (first_col..).zip(col_codes) {
+            self.set_col(col, *col_code);
+        }
+    }
+
+    /// cols of a row brighter than `level`, bit `col` for each col.
+    ///
+    /// a row scanned for every level, lights each pixel
+    /// for as many levels as its brightness.
+    #[inline]
+    pub fn row(&self, row: u8, level: u8) -> u8 {
+        self.pixels[row as usize]
+            .iter()
+            .enumerate()
+            .filter(|(_, brightness)| **brightness > level)
+            .fold(0, |cols, (col, _)| cols | 1 << col)
+    }
+}
+
+#[cfg(test)]
+mod test_frame {
+    use super::*;
+
+    #[test]
+    fn test_draw() {
+        let mut frame = Frame::new();
+        frame.set(&(1, 3));
+        frame.set_cols(3, &[0b00001, 0b10000]);
+        assert!(frame.is_lit(&(1, 3)));
+        assert!(frame.is_lit(&(0, 3)));
+        assert!(frame.is_lit(&(4, 4)));
+        assert!(!frame.is_lit(&(0, 4)));
+        assert_eq!(
+            (0..SIZE).map(|row| frame.row(row, 0)).collect::<Vec<_>>(),
+            [0b01000, 0b01000, 0, 0, 0b10000]
+        );
+    }
+
+    #[test]
+    fn test_brightness() {
+        let mut frame = Frame::new();
+        frame.set_brightness(&(2, 0), 3);
+        frame.set_brightness(&(2, 4), 100);
+        assert_eq!(frame.brightness(&(2, 0)), 3);
+        assert_eq!(frame.brightness(&(2, 4)), MAX_BRIGHTNESS);
+        // lit for 3 of the levels
+        let cols: Vec<_> = (0..MAX_BRIGHTNESS)
+            .map(|level| frame.row(2, level))
+            .collect();
+        assert_eq!(cols[..3], [0b10001; 3]);
+        assert_eq!(cols[3..], [0b10000; 6]);
+        frame.set_brightness(&(2, 0), 0);
+        assert!(!frame.is_lit(&(2, 0)));
+    }
+}
@@ -0,0 +1,1024 @@
+//! The state machine of the main game.
+pub use self::{
+    s0_idle::IdleKey,
+    s2_game::{Players, Trail},
+    s3_result::LetterKey,
+    s5_menu::MenuOption,
+    s6_pause::PAUSE,
+};
+use crate::{
+    Animation, ButtonInput, Device, DotState, FalseStart, Frame, GameClock, LedMatrix,
+    RandomSource, Settings, SoundSink, Track, RESTART,
+};
+
+mod s0_idle;
+mod s1_ready;
+mod s2_game;
+mod s3_result;
+mod s4_false_start;
+mod s5_menu;
+mod s6_pause;
+
+/// timers running at once, the count down and its grace.
+pub const GAME_TIMERS: usize = 2;
+
+/// Timers of the game states, started on entering a state, and
+/// cancelled on leaving it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameTimer {
+    /// the opponent selected when idle is hidden.
+    HideOpponent,
+    /// the next digit of the count down, or of the resume.
+    Digit,
+    /// presses right after the count down starts are not false starts.
+    Grace,
+    /// the dot steps.
+    Step,
+    /// the next round can start, after the result.
+    Restart,
+}
+
+/// The state machine of the game.
+pub enum Game {
+    /// Idle animation after device is started.
+    IdleAnimation {
+        /// position of the dot
+        dot: DotState,
+        /// the running dot
+        animation: Animation<IdleKey>,
+        /// B presses in a row, to change the opponent.
+        taps: u8,
+    },
+    /// Count down animation after both players are ready.
+    ReadyAnimation {
+        /// count down (initialized as the countdown setting).
+        count_down: u8,
+        /// starting position of the dot, moved by false start penalties.
+        dot: DotState,
+    },
+    /// On-going game.
+    Playing {
+        /// position of the dot
+        dot: DotState,
+        /// previous positions of the dot
+        trail: Trail,
+    },
+    /// Result animation
+    Result {
+        /// who wins
+        winner: Players,
+        /// the letter of the winner, finished after a second
+        /// to wait before ready again.
+        animation: Animation<LetterKey>,
+    },
+    /// Settings menu, opened by a long press of A when idle.
+    SettingsMenu {
+        /// the option shown
+        option: MenuOption,
+        /// A has been released after opening the menu.
+        armed: bool,
+    },
+    /// A round paused by holding A and B for a second.
+    Paused {
+        /// position of the dot
+        dot: DotState,
+        /// previous positions of the dot
+        trail: Trail,
+        /// the last pressed button is A.
+        last_a: bool,
+        /// A and B are still held since the pause.
+        held: bool,
+        /// digit of the count down, 0 until both are pressed.
+        resume: u8,
+    },
+    /// A player pressed during the count down.
+    FalseStart {
+        /// who pressed too early
+        offender: Players,
+        /// the blinking letter of the offender
+        animation: Animation<LetterKey>,
+        /// starting position of the dot
+        dot: DotState,
+    },
+}
+
+impl Game {
+    /// initial state of the game.
+    pub const fn new() -> Self {
+        Game::IdleAnimation {
+            animation: s0_idle::animation(),
+            dot: DotState::new(),
+            taps: 0,
+        }
+    }
+
+    /// advance the game by one tick, and show its frame.
+    ///
+    /// returns the winner when a round finishes.
+    pub fn poll<B, M, S, R>(
+        &mut self,
+        clock: &mut impl GameClock,
+        device: &mut Device<B, M, S, R>,
+        settings: &mut Settings,
+    ) -> Option<Players>
+    where
+        B: ButtonInput,
+        M: LedMatrix,
+        S: SoundSink,
+        R: RandomSource,
+    {
+        clock.ack_tick();
+        let winner = self.update(clock, device, settings);
+        let mut frame = Frame::new();
+        self.draw(clock, settings, &mut frame);
+        device.display.show(&frame);
+        winner
+    }
+
+    /// draw the current state, at the time of `clock`.
+    pub fn draw(&self, clock: &impl GameClock, settings: &Settings, frame: &mut Frame) {
+        match self {
+            Game::IdleAnimation { animation, dot, .. } => {
+                let shown = clock.remaining(GameTimer::HideOpponent).is_some();
+                s0_idle::draw_idle_animation(animation, dot, shown, settings, frame)
+            }
+            Game::ReadyAnimation { count_down, .. } => {
+                s1_ready::draw_ready_animation(*count_down, frame)
+            }
+            Game::Playing { dot, trail } => {
+                let step = settings.speed.step();
+                let next = clock.remaining(GameTimer::Step).unwrap_or(step);
+                s2_game::draw_game(next, dot, trail, step, frame)
+            }
+            Game::Result { winner, animation } => {
+                s3_result::draw_result_animation(animation, *winner, frame)
+            }
+            Game::FalseStart {
+                offender,
+                animation,
+                ..
+            } => s4_false_start::draw_false_start_animation(animation, *offender, frame),
+            Game::SettingsMenu { option, .. } => {
+                s5_menu::draw_settings_menu(*option, settings, frame)
+            }
+            Game::Paused { resume, .. } => s6_pause::draw_paused(*resume, frame),
+        }
+    }
+
+    /// whether the round winner is shown, for half a second of the result.
+    #[inline]
+    pub fn winner_shown(&self) -> bool {
+        match self {
+            Game::Result { animation, .. } => animation.key() == LetterKey::Shown,
+            _ => false,
+        }
+    }
+
+    /// the dot of the round, or of the idle animation.
+    pub fn dot(&self) -> Option<&DotState> {
+        match self {
+            Game::IdleAnimation { dot, .. }
+            | Game::ReadyAnimation { dot, .. }
+            | Game::Playing { dot, .. }
+            | Game::FalseStart { dot, .. }
+            | Game::Paused { dot, .. } => Some(dot),
+            Game::Result { .. } | Game::SettingsMenu { .. } => None,
+        }
+    }
+
+    /// the winner of the round, while shown.
+    #[inline]
+    pub fn winner(&self) -> Option<Players> {
+        match self {
+            Game::Result { winner, .. } => Some(*winner),
+            _ => None,
+        }
+    }
+
+    fn update<B, M, S, R>(
+        &mut self,
+        clock: &mut impl GameClock,
+        device: &mut Device<B, M, S, R>,
+        settings: &mut Settings,
+    ) -> Option<Players>
+    where
+        B: ButtonInput,
+        S: SoundSink,
+        R: RandomSource,
+    {
+        let Device {
+            buttons,
+            sound,
+            rng,
+            ..
+        } = device;
+        let sound = &mut Switched {
+            sound,
+            on: settings.sound,
+        };
+        // the timers of a state never fire at the same tick
+        let timer = clock.poll_timer();
+        let gesture = buttons.take_gesture();
+        if gesture == Some(RESTART) && !matches!(self, Game::IdleAnimation { .. }) {
+            clock.cancel_timers();
+            sound.stop();
+            buttons.reset();
+            *self = Self::new();
+            return None;
+        }
+        match self {
+            Game::IdleAnimation {
+                animation,
+                dot,
+                taps,
+            } => {
+                if s0_idle::open_menu(gesture) {
+                    clock.cancel_timers();
+                    *self = Game::SettingsMenu {
+                        option: MenuOption::Mode,
+                        armed: false,
+                    };
+                    return None;
+                }
+                if s0_idle::select_opponent(taps, settings, buttons, clock) {
+                    return None;
+                }
+                if s0_idle::idle_animation(animation, dot, buttons) {
+                    *self = Self::ready_animation(settings.countdown, clock, sound);
+                }
+            }
+            Game::ReadyAnimation { count_down, dot } => {
+                if settings.false_start != FalseStart::Off {
+                    let grace = clock.remaining(GameTimer::Grace).is_some();
+                    if let Some(offender) = s1_ready::false_start(grace, buttons) {
+                        clock.cancel_timers();
+                        sound.play_track(Track::Buzz);
+                        *self = Game::FalseStart {
+                            offender,
+                            animation: s4_false_start::animation(),
+                            dot: core::mem::take(dot),
+                        };
+                        return None;
+                    }
+                }
+                if s1_ready::ready_animation(timer, count_down, sound) {
+                    *self =
+                        Self::playing(core::mem::take(dot), settings, rng, buttons, sound, clock);
+                }
+            }
+            Game::Playing { dot, trail } => {
+                let last_a = buttons.last_a();
+                if s6_pause::pause(gesture, buttons, sound, clock) {
+                    *self = Game::Paused {
+                        dot: core::mem::take(dot),
+                        trail: *trail,
+                        last_a,
+                        held: gesture != Some(PAUSE),
+                        resume: 0,
+                    };
+                    return None;
+                }
+                if let Some(winner) = s2_game::game(timer, dot, trail, settings.rule, buttons) {
+                    *self = Self::result(winner, sound, clock);
+                    return Some(winner);
+                }
+            }
+            Game::Result { animation, .. } => {
+                let delayed = clock.remaining(GameTimer::Restart).is_some();
+                if s3_result::result_animation(delayed, animation, buttons) {
+                    *self = Self::ready_animation(settings.countdown, clock, sound)
+                }
+            }
+            Game::FalseStart {
+                offender,
+                animation,
+                dot,
+            } => {
+                if s4_false_start::false_start_animation(animation) {
+                    let offender = *offender;
+                    let winner = match settings.false_start {
+                        FalseStart::Forfeit => Some(offender.opponent()),
+                        FalseStart::Penalty => s2_game::penalize(dot, offender),
+                        FalseStart::Off => None,
+                    };
+                    if let Some(winner) = winner {
+                        *self = Self::result(winner, sound, clock);
+                        return Some(winner);
+                    }
+                    s1_ready::start_countdown(clock, sound);
+                    *self = Game::ReadyAnimation {
+                        count_down: settings.countdown,
+                        dot: core::mem::take(dot),
+                    };
+                }
+            }
+            Game::Paused {
+                dot,
+                trail,
+                last_a,
+                held,
+                resume,
+            } => {
+                if s6_pause::paused(gesture, timer, held, resume, buttons, sound, clock) {
+                    // the last pressed button is kept for the resumed round
+                    if *last_a {
+                        buttons.set_last_a();
+                    }
+                    s2_game::start_steps(settings.speed.step(), clock);
+                    *self = Game::Playing {
+                        dot: core::mem::take(dot),
+                        trail: *trail,
+                    };
+                }
+            }
+            Game::SettingsMenu { option, armed } => {
+                if s5_menu::settings_menu(option, armed, settings, buttons) {
+                    *self = Self::new();
+                }
+            }
+        }
+        None
+    }
+
+    /// start of a round, counting down `countdown` seconds from the
+    /// time of `clock`.
+    pub fn ready_animation(
+        countdown: u8,
+        clock: &mut impl GameClock,
+        sound: &mut impl SoundSink,
+    ) -> Self {
+        s1_ready::start_countdown(clock, sound);
+        Game::ReadyAnimation {
+            count_down: countdown,
+            dot: DotState::new(),
+        }
+    }
+
+    fn playing(
+        mut dot: DotState,
+        settings: &Settings,
+        rng: &mut impl RandomSource,
+        buttons: &mut impl ButtonInput,
+        sound: &mut impl SoundSink,
+        clock: &mut impl GameClock,
+    ) -> Self {
+        buttons.reset();
+        if let 0..=127 = rng.random_u8() {
+            dot.toggle_clockwise();
+            buttons.set_last_a();
+        }
+        sound.play_track(Track::DiHi);
+        s2_game::start_steps(settings.speed.step(), clock);
+        Game::Playing {
+            dot,
+            trail: Trail::new(),
+        }
+    }
+
+    fn result(winner: Players, sound: &mut impl SoundSink, clock: &mut impl GameClock) -> Self {
+        sound.play_track(Track::Peppa);
+        s3_result::delay_restart(clock);
+        Game::Result {
+            winner,
+            animation: s3_result::animation(),
+        }
+    }
+}
+
+/// Plays nothing when the sound is off.
+struct Switched<'a, S> {
+    sound: &'a mut S,
+    on: bool,
+}
+
+impl<S: SoundSink> SoundSink for Switched<'_, S> {
+    #[inline]
+    fn play_track(&mut self, track: Track) {
+        if self.on {
+            self.sound.play_track(track)
+        }
+    }
+
+    #[inline]
+    fn stop(&mut self) {
+        self.sound.stop()
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_game {
+    use super::*;
+    use crate::{
+        fake::*, BestOf, Chord, Difficulty, FalseStart, Gesture, InputEvent, Rule, Speed,
+        TickClock, CENTER, HOLD, MAX_BRIGHTNESS,
+    };
+
+    fn start(game: &mut Game, tick: &mut TickClock, device: &mut FakeDevice) {
+        start_with(game, tick, device, &mut Settings::new())
+    }
+
+    fn start_with(
+        game: &mut Game,
+        tick: &mut TickClock,
+        device: &mut FakeDevice,
+        settings: &mut Settings,
+    ) {
+        device.buttons.press_a(true);
+        game.poll(tick, device, settings);
+        assert!(matches!(game, Game::ReadyAnimation { .. }));
+        device.buttons.reset();
+        for _ in 0..3 * 256 {
+            game.poll(tick, device, settings);
+        }
+        assert!(matches!(game, Game::Playing { .. }));
+    }
+
+    /// poll until the game finishes, return the winner.
+    fn play(game: &mut Game, tick: &mut TickClock, device: &mut FakeDevice, a: bool) -> Players {
+        for _ in 0..10_000 {
+            if a {
+                device.buttons.press_a(false);
+            } else {
+                device.buttons.press_b(false);
+            }
+            game.poll(tick, device, &mut Settings::new());
+            if let Game::Result { winner, .. } = game {
+                return *winner;
+            }
+        }
+        panic!("game never finished")
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut settings = Settings::new();
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start(&mut game, &mut tick, &mut device);
+        for _ in 0..40 {
+            device.buttons.press_a(false);
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        let Game::Playing { dot, .. } = &game else {
+            panic!("not playing")
+        };
+        let px = *dot.px();
+
+        device.buttons.set_gesture(PAUSE);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::Paused { resume: 0, .. }));
+        assert_eq!(device.sound.1, 1);
+        // two bars
+        assert_eq!(device.display.lit().len(), 10);
+        // presses while paused do not count
+        for _ in 0..1000 {
+            device.buttons.press_b(false);
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert!(matches!(game, Game::Paused { resume: 0, .. }));
+
+        device.buttons.press_a(true);
+        for _ in 0..3 * 256 {
+            game.poll(&mut tick, &mut device, &mut settings);
+            assert!(matches!(game, Game::Paused { .. }));
+        }
+        game.poll(&mut tick, &mut device, &mut settings);
+        let Game::Playing { dot, .. } = &game else {
+            panic!("not resumed")
+        };
+        assert_eq!(*dot.px(), px);
+        // a full step after the resume
+        let step = settings.speed.step();
+        assert_eq!(tick.remaining(GameTimer::Step), Some(step));
+        assert!(device.buttons.last_a());
+        assert!(device
+            .sound
+            .0
+            .ends_with(&[Track::DiLo, Track::DiLo, Track::DiLo, Track::DiHi]));
+    }
+
+    /// buttons of both teams down or up, through the gestures of the latch.
+    fn buttons(tick: &TickClock, device: &mut FakeDevice, down: bool) {
+        let at = tick.now();
+        for player in [Players::A, Players::B] {
+            device.buttons.handle_event(&match down {
+                true => InputEvent::ButtonDown {
+                    player,
+                    member: 0,
+                    at,
+                },
+                false => InputEvent::ButtonUp {
+                    player,
+                    member: 0,
+                    at,
+                },
+            });
+        }
+    }
+
+    /// poll the latch and the game `ticks` times, return the positions
+    /// of the dot.
+    fn hold(
+        game: &mut Game,
+        tick: &mut TickClock,
+        device: &mut FakeDevice,
+        ticks: u32,
+    ) -> Vec<(u8, u8)> {
+        (0..ticks)
+            .filter_map(|_| {
+                let now = tick.now();
+                while device.buttons.poll(now).is_some() {}
+                game.poll(tick, device, &mut Settings::new());
+                game.dot().map(|dot| *dot.px())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pause_on_hold() {
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start(&mut game, &mut tick, &mut device);
+        hold(&mut game, &mut tick, &mut device, 100);
+
+        // the dot runs while held, until paused a second after the press
+        buttons(&tick, &mut device, true);
+        let held = hold(&mut game, &mut tick, &mut device, HOLD.ticks());
+        assert!(matches!(game, Game::Playing { .. }));
+        assert!(held.windows(2).any(|px| px[0] != px[1]));
+        hold(&mut game, &mut tick, &mut device, 1);
+        assert!(matches!(game, Game::Paused { held: true, .. }));
+        // the release does not resume
+        hold(&mut game, &mut tick, &mut device, 64);
+        buttons(&tick, &mut device, false);
+        hold(&mut game, &mut tick, &mut device, 64);
+        assert!(matches!(
+            game,
+            Game::Paused {
+                held: false,
+                resume: 0,
+                ..
+            }
+        ));
+        // a tap of both resumes
+        buttons(&tick, &mut device, true);
+        hold(&mut game, &mut tick, &mut device, 10);
+        buttons(&tick, &mut device, false);
+        hold(&mut game, &mut tick, &mut device, 1);
+        assert!(matches!(game, Game::Paused { resume: 3, .. }));
+        // presses while paused are discarded
+        device.buttons.press_a(false);
+        device.buttons.press_a(false);
+        hold(&mut game, &mut tick, &mut device, 768);
+        assert!(matches!(game, Game::Playing { .. }));
+        assert_eq!(device.buttons.take_presses(), (0, 0));
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut settings = Settings::new();
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start(&mut game, &mut tick, &mut device);
+        device.buttons.set_gesture(RESTART);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::IdleAnimation { .. }));
+        assert_eq!(device.sound.1, 1);
+        // ignored when idle
+        device.buttons.set_gesture(RESTART);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert_eq!(device.sound.1, 1);
+    }
+
+    #[test]
+    fn test_idle_until_both_pressed() {
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        device.buttons.press_a(false);
+        device.buttons.press_b(false);
+        for _ in 0..1000 {
+            game.poll(&mut tick, &mut device, &mut Settings::new());
+        }
+        assert!(matches!(game, Game::IdleAnimation { .. }));
+        assert_eq!(device.display.lit().len(), 1);
+        assert_eq!(tick.ticks(), 1000);
+    }
+
+    #[test]
+    fn test_select_opponent() {
+        let mut settings = Settings::new();
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+
+        // A breaks the taps of B
+        for press_a in [false, true, false, false, false] {
+            if press_a {
+                device.buttons.press_a(false);
+            } else {
+                device.buttons.press_b(false);
+            }
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert_eq!(settings.bot, Some(Difficulty::Easy));
+        // the easy bot, a bar one row high in the middle column
+        assert_eq!(device.display.lit(), [(4, 2)]);
+
+        for _ in 0..3 {
+            device.buttons.press_b(false);
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert_eq!(settings.bot, Some(Difficulty::Medium));
+        // two rows high
+        assert_eq!(device.display.lit(), [(3, 2), (4, 2)]);
+
+        // back to the running dot
+        for _ in 0..256 {
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert!(matches!(game, Game::IdleAnimation { .. }));
+        assert_eq!(tick.remaining(GameTimer::HideOpponent), None);
+        assert_eq!(device.display.lit().len(), 1);
+        device.buttons.press_a(true);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::ReadyAnimation { .. }));
+        assert_eq!(settings.bot, Some(Difficulty::Medium));
+    }
+
+    #[test]
+    fn test_settings_menu() {
+        let mut settings = Settings::new();
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+
+        // long press of A
+        device.buttons.set_gesture(Gesture::LongPress(Chord::B));
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::IdleAnimation { .. }));
+        device.buttons.press_a(false);
+        device.buttons.hold_a(true);
+        device.buttons.set_gesture(Gesture::LongPress(Chord::A));
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(
+            game,
+            Game::SettingsMenu {
+                option: MenuOption::Mode,
+                ..
+            }
+        ));
+        // M and two dots for two players
+        assert_eq!(device.display.lit().len(), 14);
+
+        // A is ignored until released
+        device.buttons.press_a(false);
+        game.poll(&mut tick, &mut device, &mut settings);
+        device.buttons.hold_a(false);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(
+            game,
+            Game::SettingsMenu {
+                option: MenuOption::Mode,
+                ..
+            }
+        ));
+
+        let mut press = |a: bool, settings: &mut Settings| {
+            if a {
+                device.buttons.press_a(false);
+            } else {
+                device.buttons.press_b(false);
+            }
+            game.poll(&mut tick, &mut device, settings);
+        };
+        press(false, &mut settings);
+        assert_eq!(settings.bot, Some(Difficulty::Easy));
+        for (a, b) in [(1, 1), (1, 2), (1, 3), (1, 1)] {
+            (0..a).for_each(|_| press(true, &mut settings));
+            (0..b).for_each(|_| press(false, &mut settings));
+        }
+        assert_eq!(
+            settings,
+            Settings {
+                rule: Rule::TapRate,
+                speed: Speed::Slow,
+                countdown: 1,
+                sound: false,
+                bot: Some(Difficulty::Easy),
+                ..Settings::new()
+            }
+        );
+
+        // the option and value
+        let mut lit = device.display.lit();
+        lit.sort_by_key(|&(x, y)| (y == 4, x, y));
+        let note = [
+            (0, 1),
+            (0, 2),
+            (1, 1),
+            (2, 1),
+            (3, 0),
+            (3, 1),
+            (4, 0),
+            (4, 1),
+        ];
+        assert_eq!(lit[..8], note);
+        // sound off
+        assert_eq!(lit[8..], [(4, 4)]);
+
+        // best of 5, three wins needed
+        for a in [true, false, false] {
+            if a {
+                device.buttons.press_a(false);
+            } else {
+                device.buttons.press_b(false);
+            }
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert_eq!(settings.best_of, BestOf::Five);
+        let value: Vec<_> = device
+            .display
+            .lit()
+            .into_iter()
+            .filter(|&(_, col)| col == 4)
+            .collect();
+        assert_eq!(value, [(2, 4), (3, 4), (4, 4)]);
+        // false starts forfeit the round
+        for a in [true, false] {
+            if a {
+                device.buttons.press_a(false);
+            } else {
+                device.buttons.press_b(false);
+            }
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert!(matches!(
+            game,
+            Game::SettingsMenu {
+                option: MenuOption::FalseStart,
+                ..
+            }
+        ));
+        assert_eq!(settings.false_start, FalseStart::Forfeit);
+        // back to the first option
+        device.buttons.press_a(false);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(
+            game,
+            Game::SettingsMenu {
+                option: MenuOption::Mode,
+                ..
+            }
+        ));
+
+        // leave with both buttons
+        device.buttons.press_a(false);
+        device.buttons.press_b(true);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::IdleAnimation { .. }));
+        assert_eq!(device.display.lit().len(), 1);
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::IdleAnimation { .. }));
+    }
+
+    #[test]
+    fn test_countdown_settings() {
+        let mut settings = Settings {
+            countdown: 5,
+            sound: false,
+            ..Settings::new()
+        };
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        device.buttons.press_a(true);
+        game.poll(&mut tick, &mut device, &mut settings);
+        for _ in 0..5 * 256 - 1 {
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert!(matches!(game, Game::ReadyAnimation { count_down: 1, .. }));
+        game.poll(&mut tick, &mut device, &mut settings);
+        assert!(matches!(game, Game::Playing { .. }));
+        assert!(device.sound.0.is_empty());
+    }
+
+    #[test]
+    fn test_countdown() {
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start(&mut game, &mut tick, &mut device);
+        assert_eq!(
+            device.sound.0,
+            [Track::DiLo, Track::DiLo, Track::DiLo, Track::DiHi]
+        );
+        // the running dot and the goal position
+        assert_eq!(device.display.lit().len(), 2);
+    }
+
+    #[test]
+    fn test_fading_trail() {
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start(&mut game, &mut tick, &mut device);
+        let brightness = |device: &FakeDevice| {
+            let mut lit: Vec<_> = device
+                .display
+                .lit()
+                .iter()
+                .filter(|px| **px != CENTER)
+                .map(|px| device.display.0.brightness(px))
+                .collect();
+            lit.sort();
+            lit
+        };
+        assert_eq!(brightness(&device), [MAX_BRIGHTNESS]);
+
+        // the dot moves at the end of each step, leaving a trail
+        for steps in 1..=4 {
+            for _ in 0..16 {
+                game.poll(&mut tick, &mut device, &mut Settings::new());
+            }
+            let trail = [4, 2, 1].into_iter().take(steps).rev();
+            let expected: Vec<_> = trail.chain([MAX_BRIGHTNESS]).collect();
+            assert_eq!(brightness(&device), expected);
+        }
+
+        // the goal position pulses once per step
+        let center: Vec<_> = (0..16)
+            .map(|_| {
+                game.poll(&mut tick, &mut device, &mut Settings::new());
+                device.display.0.brightness(&CENTER)
+            })
+            .collect();
+        assert_eq!(center[7], 1);
+        assert_eq!(center[15], MAX_BRIGHTNESS);
+        assert!(center[..8].windows(2).all(|w| w[0] >= w[1]));
+        assert!(center[7..].windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_players_win() {
+        for (random, a) in [(0, true), (0, false), (255, true), (255, false)] {
+            let mut game = Game::new();
+            let mut tick = TickClock::new();
+            let mut device = fake_device(random);
+            start(&mut game, &mut tick, &mut device);
+            let winner = play(&mut game, &mut tick, &mut device, a);
+            assert_eq!(winner, if a { Players::A } else { Players::B });
+            assert_eq!(device.sound.0.last(), Some(&Track::Peppa));
+        }
+    }
+
+    #[test]
+    fn test_restart_after_one_sec() {
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start(&mut game, &mut tick, &mut device);
+        play(&mut game, &mut tick, &mut device, true);
+        device.buttons.press_b(true);
+        for _ in 0..256 {
+            game.poll(&mut tick, &mut device, &mut Settings::new());
+        }
+        assert!(matches!(game, Game::Result { .. }));
+        device.buttons.press_b(true);
+        game.poll(&mut tick, &mut device, &mut Settings::new());
+        assert!(matches!(game, Game::ReadyAnimation { .. }));
+        // the letter is replaced by the count down
+        assert_eq!(device.display.lit().len(), 10);
+    }
+
+    #[test]
+    fn test_tap_rate() {
+        let mut settings = Settings {
+            rule: Rule::TapRate,
+            ..Settings::new()
+        };
+        let dot_px = |game: &Game| match game {
+            Game::Playing { dot, .. } => *dot.px(),
+            _ => panic!("not playing"),
+        };
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        start_with(&mut game, &mut tick, &mut device, &mut settings);
+
+        // the dot holds without presses
+        let start_px = dot_px(&game);
+        for _ in 0..16 * 8 {
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        assert_eq!(dot_px(&game), start_px);
+
+        // B presses last, but A presses more
+        for i in 0..10_000 {
+            match i % 16 {
+                0 | 8 => device.buttons.press_a(false),
+                12 => device.buttons.press_b(false),
+                _ => (),
+            }
+            if let Some(winner) = game.poll(&mut tick, &mut device, &mut settings) {
+                assert_eq!(winner, Players::A);
+                return;
+            }
+        }
+        panic!("game never finished")
+    }
+
+    /// press B in the middle of the count down.
+    fn false_start(
+        game: &mut Game,
+        tick: &mut TickClock,
+        device: &mut FakeDevice,
+        settings: &mut Settings,
+    ) {
+        device.buttons.press_a(true);
+        game.poll(tick, device, settings);
+        for _ in 0..256 {
+            game.poll(tick, device, settings);
+        }
+        device.buttons.press_b(false);
+        game.poll(tick, device, settings);
+        assert!(matches!(
+            game,
+            Game::FalseStart {
+                offender: Players::B,
+                ..
+            }
+        ));
+        assert_eq!(device.sound.0.last(), Some(&Track::Buzz));
+        for _ in 0..255 {
+            game.poll(tick, device, settings);
+        }
+        assert!(matches!(game, Game::FalseStart { .. }));
+        assert!(device.display.lit().is_empty());
+    }
+
+    #[test]
+    fn test_false_start_forfeit() {
+        let mut settings = Settings {
+            false_start: FalseStart::Forfeit,
+            ..Settings::new()
+        };
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        false_start(&mut game, &mut tick, &mut device, &mut settings);
+        assert_eq!(
+            game.poll(&mut tick, &mut device, &mut settings),
+            Some(Players::A)
+        );
+        assert_eq!(device.sound.0.last(), Some(&Track::Peppa));
+    }
+
+    #[test]
+    fn test_false_start_penalty() {
+        let mut settings = Settings {
+            false_start: FalseStart::Penalty,
+            ..Settings::new()
+        };
+        let mut game = Game::new();
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        false_start(&mut game, &mut tick, &mut device, &mut settings);
+        assert_eq!(game.poll(&mut tick, &mut device, &mut settings), None);
+        assert!(matches!(game, Game::ReadyAnimation { count_down: 3, .. }));
+
+        // the count down starts over, the dot starts towards the goal of A
+        for _ in 0..3 * 256 {
+            game.poll(&mut tick, &mut device, &mut settings);
+        }
+        match &game {
+            Game::Playing { dot, .. } => assert_ne!(*dot.px(), *DotState::new().px()),
+            _ => panic!("not playing"),
+        }
+        let mut steps = 0;
+        while game.poll(&mut tick, &mut device, &mut settings).is_none() {
+            if tick.remaining(GameTimer::Step) == Some(settings.speed.step()) {
+                steps += 1;
+            }
+        }
+        assert!(matches!(
+            game,
+            Game::Result {
+                winner: Players::A,
+                ..
+            }
+        ));
+        // the dot heads to A at random 0, the steps before the one
+        // ending the round, one less than from the start.
+        assert_eq!(steps, 23);
+    }
+}
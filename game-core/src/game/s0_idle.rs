@@ -0,0 +1,114 @@
+//! Idle animation, before game starts.
+use super::{s5_menu::opponent_glyph, GameTimer};
+use crate::{
+    Animation, ButtonInput, Chord, DotState, Duration, Frame, GameClock, Gesture, Keyframe, Repeat,
+    Settings, TimerMode, CENTER,
+};
+
+/// B presses in a row, without A, to change the opponent.
+const SELECT_TAPS: u8 = 3;
+/// time to show the selected opponent.
+const SHOW: Duration = Duration::from_secs(1);
+/// col showing the selected opponent.
+const SHOW_COL: u8 = 2;
+
+/// What the idle animation shows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IdleKey {
+    Dot,
+    Center,
+}
+
+/// the dot with the center flashing twice, the dot moves after the last
+/// keyframe, a step every 16 ticks.
+const KEYFRAMES: [Keyframe<IdleKey>; 4] = [
+    Keyframe::new(IdleKey::Dot, Duration::from_ticks(7)),
+    Keyframe::new(IdleKey::Center, Duration::from_ticks(1)),
+    Keyframe::new(IdleKey::Dot, Duration::from_ticks(7)),
+    Keyframe::new(IdleKey::Center, Duration::from_ticks(1)),
+];
+
+pub const fn animation() -> Animation<IdleKey> {
+    Animation::new(&KEYFRAMES, Repeat::Loop)
+}
+
+/// The animation when idle.
+///
+/// returns true if started.
+pub fn idle_animation(
+    animation: &mut Animation<IdleKey>,
+    dot: &mut DotState,
+    buttons: &impl ButtonInput,
+) -> bool {
+    // move the running dot
+    if animation.poll() {
+        dot_idle_spiral(dot);
+    }
+
+    game_started(buttons)
+}
+
+/// the running dot, with the center flashing twice per step,
+/// or the opponent while it is shown.
+pub fn draw_idle_animation(
+    animation: &Animation<IdleKey>,
+    dot: &DotState,
+    shown: bool,
+    settings: &Settings,
+    frame: &mut Frame,
+) {
+    if shown {
+        frame.set_col(SHOW_COL, opponent_glyph(settings.bot));
+        return;
+    }
+    match animation.key() {
+        IdleKey::Dot => frame.set(dot.px()),
+        IdleKey::Center => frame.set(&CENTER),
+    }
+}
+
+/// Tap B three times to change the opponent of A,
+/// which is shown for a second instead of the running dot.
+///
+/// returns true while showing the opponent.
+pub fn select_opponent(
+    taps: &mut u8,
+    settings: &mut Settings,
+    buttons: &mut impl ButtonInput,
+    clock: &mut impl GameClock,
+) -> bool {
+    let (presses_a, presses_b) = buttons.take_presses();
+    if presses_a > 0 {
+        *taps = 0;
+    } else {
+        *taps = taps.saturating_add(presses_b);
+    }
+    if *taps >= SELECT_TAPS {
+        *taps = 0;
+        settings.cycle_bot();
+        clock.start_timer(GameTimer::HideOpponent, SHOW, TimerMode::OneShot);
+    } else if game_started(buttons) {
+        clock.cancel_timers();
+    }
+    clock.remaining(GameTimer::HideOpponent).is_some()
+}
+
+/// Long press A alone to open the settings menu.
+///
+/// returns true if the menu opens.
+#[inline]
+pub fn open_menu(gesture: Option<Gesture>) -> bool {
+    gesture == Some(Gesture::LongPress(Chord::A))
+}
+
+/// movement of dot in idle state.
+#[inline]
+fn dot_idle_spiral(dot: &mut DotState) {
+    dot.spiral(|dot| dot.toggle_clockwise());
+}
+
+/// Check start.
+#[inline]
+fn game_started(buttons: &impl ButtonInput) -> bool {
+    buttons.both_pressed()
+}
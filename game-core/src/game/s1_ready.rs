@@ -0,0 +1,60 @@
+use super::{s2_game::Players, GameTimer};
+use crate::{draw_glyph, ButtonInput, Duration, Frame, GameClock, SoundSink, TimerMode, Track};
+
+/// presses right after the countdown starts are part of the start.
+const GRACE: Duration = Duration::from_ms(125);
+/// time each digit of the count down is shown.
+pub(super) const DIGIT: Duration = Duration::from_secs(1);
+
+/// Start the count down, a beep and a digit each second.
+pub fn start_countdown(clock: &mut impl GameClock, sound: &mut impl SoundSink) {
+    clock.cancel_timers();
+    clock.start_timer(GameTimer::Digit, DIGIT, TimerMode::Periodic);
+    clock.start_timer(GameTimer::Grace, GRACE, TimerMode::OneShot);
+    sound.play_track(Track::DiLo);
+}
+
+/// The animation when when ready.
+///
+/// returns true if countdown finishes.
+pub fn ready_animation(
+    timer: Option<GameTimer>,
+    count_down: &mut u8,
+    sound: &mut impl SoundSink,
+) -> bool {
+    if timer != Some(GameTimer::Digit) {
+        return false;
+    }
+    *count_down -= 1;
+    if *count_down > 0 {
+        sound.play_track(Track::DiLo);
+    }
+
+    // count down finished
+    *count_down == 0
+}
+
+/// the digit of the count down in cols 1 to 4.
+#[inline]
+pub fn draw_ready_animation(count_down: u8, frame: &mut Frame) {
+    draw_glyph(char::from(b'0' + count_down), 1, frame);
+}
+
+/// Check presses during the countdown.
+///
+/// returns the offender, if any.
+/// `grace` while the presses are part of the start.
+pub fn false_start(grace: bool, buttons: &mut impl ButtonInput) -> Option<Players> {
+    let (presses_a, presses_b) = buttons.take_presses();
+    if grace {
+        return None;
+    }
+    let offender = match (presses_a > 0, presses_b > 0) {
+        (true, true) if buttons.last_a() => Players::A,
+        (true, true) => Players::B,
+        (true, false) => Players::A,
+        (false, true) => Players::B,
+        (false, false) => return None,
+    };
+    Some(offender)
+}
@@ -0,0 +1,173 @@
+//! The running dot of a round, moved by the presses of the players.
+use core::{cmp::Ordering, sync::atomic::compiler_fence};
+
+use super::GameTimer;
+use crate::{
+    ButtonInput, DotState, Duration, Frame, GameClock, Rule, TimerMode, CENTER, MAX_BRIGHTNESS,
+};
+
+/// positions left behind by the dot.
+const TRAIL_LEN: usize = 3;
+/// dimmest brightness of the pulsing goal position.
+const PULSE_MIN: u8 = 1;
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Players {
+    A = 0,
+    B = 1,
+}
+
+impl Players {
+    /// the letter of the player, as on the board.
+    #[inline]
+    pub fn letter(self) -> char {
+        match self {
+            Players::A => 'A',
+            Players::B => 'B',
+        }
+    }
+
+    #[inline]
+    pub fn opponent(self) -> Self {
+        match self {
+            Players::A => Players::B,
+            Players::B => Players::A,
+        }
+    }
+}
+
+/// Previous positions of the dot, fading out, the latest first.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Trail([Option<(u8, u8)>; TRAIL_LEN]);
+
+impl Trail {
+    pub const fn new() -> Self {
+        Self([None; TRAIL_LEN])
+    }
+
+    #[inline]
+    fn push(&mut self, px: (u8, u8)) {
+        self.0.rotate_right(1);
+        self.0[0] = Some(px);
+    }
+
+    /// the positions with their brightness, halved at each step.
+    #[inline]
+    fn pixels(&self) -> impl Iterator<Item = ((u8, u8), u8)> + '_ {
+        (1..)
+            .zip(&self.0)
+            .filter_map(|(age, px)| px.map(|px| (px, MAX_BRIGHTNESS >> age)))
+    }
+}
+
+/// Run the dot, a step every `step`.
+#[inline]
+pub fn start_steps(step: Duration, clock: &mut impl GameClock) {
+    clock.cancel_timers();
+    clock.start_timer(GameTimer::Step, step, TimerMode::Periodic);
+}
+
+/// The animation when playing.
+pub fn game(
+    timer: Option<GameTimer>,
+    dot: &mut DotState,
+    trail: &mut Trail,
+    rule: Rule,
+    buttons: &mut impl ButtonInput,
+) -> Option<Players> {
+    if timer != Some(GameTimer::Step) {
+        return None;
+    }
+    // move the running dot
+    let px = *dot.px();
+    if let Some(winner) = dot_game_spiral(dot, rule, buttons) {
+        return Some(winner);
+    }
+    if *dot.px() != px {
+        trail.push(px);
+    }
+    None
+}
+
+/// the running dot with its fading trail, and the goal position
+/// pulsing once per step, brightest when the dot moves.
+///
+/// `next` is the time until the next step.
+#[inline]
+pub fn draw_game(next: Duration, dot: &DotState, trail: &Trail, step: Duration, frame: &mut Frame) {
+    trail
+        .pixels()
+        .for_each(|(px, brightness)| frame.set_brightness(&px, brightness));
+    let (next, step) = (next.ticks().min(step.ticks()), step.ticks());
+    // ticks since the last step, against half a step
+    let phase = (2 * (step - next)).abs_diff(step);
+    let pulse = (MAX_BRIGHTNESS - PULSE_MIN) as u32 * phase / step;
+    frame.set_brightness(&CENTER, PULSE_MIN + pulse as u8);
+    frame.set(dot.px());
+}
+
+/// compute the next dot position for game.
+fn dot_game_spiral(
+    dot: &mut DotState,
+    rule: Rule,
+    buttons: &mut impl ButtonInput,
+) -> Option<Players> {
+    let mut result = None;
+
+    // presses since last step
+    let (presses_a, presses_b) = buttons.take_presses();
+    let towards_a = match rule {
+        Rule::LastPress => buttons.last_a(),
+        Rule::TapRate => match presses_a.cmp(&presses_b) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            // hold position on a tie
+            Ordering::Equal => return None,
+        },
+    };
+    update_dot_motion(dot, towards_a);
+    compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    dot.spiral(|dot| {
+        if dot.is_left() {
+            result = Some(Players::A)
+        } else {
+            result = Some(Players::B)
+        }
+        // turn away from the end to prevent overflow,
+        // the dot might have just turned towards it.
+        if dot.is_left() != dot.is_clockwise() {
+            dot.toggle_clockwise();
+        }
+    });
+
+    result
+}
+
+/// move the dot one step towards the goal of the opponent of
+/// the offender, keeping its direction.
+///
+/// returns the opponent if the dot reaches its goal.
+pub fn penalize(dot: &mut DotState, offender: Players) -> Option<Players> {
+    let mut result = None;
+    let clockwise = dot.is_clockwise();
+    update_dot_motion(dot, offender == Players::B);
+    dot.spiral(|dot| {
+        result = Some(offender.opponent());
+        if dot.is_left() != dot.is_clockwise() {
+            dot.toggle_clockwise();
+        }
+    });
+    if dot.is_clockwise() != clockwise {
+        dot.toggle_clockwise();
+    }
+    result
+}
+
+/// update dot motion
+#[inline]
+fn update_dot_motion(dot: &mut DotState, towards_a: bool) {
+    if let (true, true) | (false, false) = (dot.is_clockwise(), towards_a) {
+        dot.toggle_clockwise();
+    }
+}
@@ -0,0 +1,57 @@
+use super::{s2_game::Players, GameTimer};
+use crate::{
+    draw_glyph, Animation, ButtonInput, Duration, Frame, GameClock, Keyframe, Repeat, TimerMode,
+};
+
+/// Whether a letter is shown.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LetterKey {
+    Shown,
+    Hidden,
+}
+
+/// the winner for half a second.
+const KEYFRAMES: [Keyframe<LetterKey>; 2] = [
+    Keyframe::new(LetterKey::Shown, Duration::from_ms(500)),
+    Keyframe::new(LetterKey::Hidden, Duration::from_ms(500)),
+];
+/// presses are ignored for a second, before the next round.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+pub const fn animation() -> Animation<LetterKey> {
+    Animation::new(&KEYFRAMES, Repeat::Once)
+}
+
+/// Allow the next round after the delay.
+#[inline]
+pub fn delay_restart(clock: &mut impl GameClock) {
+    clock.cancel_timers();
+    clock.start_timer(GameTimer::Restart, RESTART_DELAY, TimerMode::OneShot);
+}
+
+/// Returns whether both buttons are pressed, once `delayed` is over.
+pub fn result_animation(
+    delayed: bool,
+    animation: &mut Animation<LetterKey>,
+    buttons: &mut impl ButtonInput,
+) -> bool {
+    animation.poll();
+    if delayed {
+        buttons.reset();
+        return false;
+    }
+    buttons.both_pressed()
+}
+
+#[inline]
+pub fn draw_result_animation(animation: &Animation<LetterKey>, winner: Players, frame: &mut Frame) {
+    if animation.key() == LetterKey::Shown {
+        draw_letter(winner, frame);
+    }
+}
+
+/// the letter of a player, A on the left and B on the right.
+#[inline]
+pub(super) fn draw_letter(player: Players, frame: &mut Frame) {
+    draw_glyph(player.letter(), player as i16, frame);
+}
@@ -0,0 +1,44 @@
+//! False start animation, when a player presses during the countdown.
+use super::{
+    s2_game::Players,
+    s3_result::{draw_letter, LetterKey},
+};
+use crate::{Animation, Duration, Frame, Keyframe, Repeat};
+
+/// the letter is shown and hidden for as long.
+const BLINK: Duration = Duration::from_ms(125);
+
+/// the letter blinking 4 times in a second.
+const KEYFRAMES: [Keyframe<LetterKey>; 8] = [
+    Keyframe::new(LetterKey::Shown, BLINK),
+    Keyframe::new(LetterKey::Hidden, BLINK),
+    Keyframe::new(LetterKey::Shown, BLINK),
+    Keyframe::new(LetterKey::Hidden, BLINK),
+    Keyframe::new(LetterKey::Shown, BLINK),
+    Keyframe::new(LetterKey::Hidden, BLINK),
+    Keyframe::new(LetterKey::Shown, BLINK),
+    Keyframe::new(LetterKey::Hidden, BLINK),
+];
+
+pub const fn animation() -> Animation<LetterKey> {
+    Animation::new(&KEYFRAMES, Repeat::Once)
+}
+
+/// Quickly blink the letter of the offender for a second.
+///
+/// returns true when finished.
+#[inline]
+pub fn false_start_animation(animation: &mut Animation<LetterKey>) -> bool {
+    animation.poll()
+}
+
+#[inline]
+pub fn draw_false_start_animation(
+    animation: &Animation<LetterKey>,
+    offender: Players,
+    frame: &mut Frame,
+) {
+    if animation.key() == LetterKey::Shown {
+        draw_letter(offender, frame);
+    }
+}
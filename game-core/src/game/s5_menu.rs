@@ -0,0 +1,149 @@
+//! Settings menu, A cycles the options and B changes the value.
+use crate::{BestOf, ButtonInput, Difficulty, FalseStart, Frame, Rule, Settings};
+
+/// by columns, the option in cols 0 to 2, the value in col 4.
+const CHAR_M: [u8; 3] = [0b11111, 0b00110, 0b11111];
+const CHAR_R: [u8; 3] = [0b11111, 0b00101, 0b11010];
+const CHAR_S: [u8; 3] = [0b10010, 0b10101, 0b01001];
+const CHAR_C: [u8; 3] = [0b01110, 0b10001, 0b10001];
+const NOTE: [u8; 3] = [0b11000, 0b11111, 0b00001];
+const CHAR_B: [u8; 3] = [0b11111, 0b10101, 0b01010];
+const CHAR_F: [u8; 3] = [0b11111, 0b00101, 0b00001];
+const VALUE_COL: u8 = 4;
+const ALL_ROWS: u8 = 0b11111;
+
+/// An option of the settings menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuOption {
+    /// two players, or against the computer.
+    Mode,
+    Rule,
+    Speed,
+    CountDown,
+    Sound,
+    /// rounds of a match.
+    BestOf,
+    /// false starts ignored, forfeit or penalized.
+    FalseStart,
+}
+
+impl MenuOption {
+    /// the next option, wrapping around.
+    #[inline]
+    fn next(self) -> Self {
+        match self {
+            MenuOption::Mode => MenuOption::Rule,
+            MenuOption::Rule => MenuOption::Speed,
+            MenuOption::Speed => MenuOption::CountDown,
+            MenuOption::CountDown => MenuOption::Sound,
+            MenuOption::Sound => MenuOption::BestOf,
+            MenuOption::BestOf => MenuOption::FalseStart,
+            MenuOption::FalseStart => MenuOption::Mode,
+        }
+    }
+
+    #[inline]
+    fn glyph(self) -> [u8; 3] {
+        match self {
+            MenuOption::Mode => CHAR_M,
+            MenuOption::Rule => CHAR_R,
+            MenuOption::Speed => CHAR_S,
+            MenuOption::CountDown => CHAR_C,
+            MenuOption::Sound => NOTE,
+            MenuOption::BestOf => CHAR_B,
+            MenuOption::FalseStart => CHAR_F,
+        }
+    }
+
+    /// change the value of the option.
+    fn change(self, settings: &mut Settings) {
+        match self {
+            MenuOption::Mode => settings.cycle_bot(),
+            MenuOption::Rule => {
+                settings.rule = match settings.rule {
+                    Rule::LastPress => Rule::TapRate,
+                    Rule::TapRate => Rule::LastPress,
+                }
+            }
+            MenuOption::Speed => settings.speed = settings.speed.next(),
+            MenuOption::CountDown => settings.cycle_countdown(),
+            MenuOption::Sound => settings.sound = !settings.sound,
+            MenuOption::BestOf => {
+                settings.best_of = match settings.best_of {
+                    BestOf::One => BestOf::Three,
+                    BestOf::Three => BestOf::Five,
+                    BestOf::Five => BestOf::Seven,
+                    BestOf::Seven => BestOf::One,
+                }
+            }
+            MenuOption::FalseStart => {
+                settings.false_start = match settings.false_start {
+                    FalseStart::Off => FalseStart::Forfeit,
+                    FalseStart::Forfeit => FalseStart::Penalty,
+                    FalseStart::Penalty => FalseStart::Off,
+                }
+            }
+        }
+    }
+
+    /// the value of the option, shown in one col.
+    fn value_glyph(self, settings: &Settings) -> u8 {
+        match self {
+            MenuOption::Mode => opponent_glyph(settings.bot),
+            MenuOption::Rule => bar(settings.rule as u8 + 1),
+            MenuOption::Speed => bar(settings.speed as u8 + 1),
+            MenuOption::CountDown => bar(settings.countdown),
+            MenuOption::Sound if settings.sound => ALL_ROWS,
+            MenuOption::Sound => bar(1),
+            MenuOption::BestOf => bar(settings.best_of.wins_needed()),
+            MenuOption::FalseStart => bar(settings.false_start as u8 + 1),
+        }
+    }
+}
+
+/// The settings menu.
+///
+/// returns true when left by pressing both buttons.
+pub fn settings_menu(
+    option: &mut MenuOption,
+    armed: &mut bool,
+    settings: &mut Settings,
+    buttons: &mut impl ButtonInput,
+) -> bool {
+    if buttons.both_pressed() {
+        buttons.reset();
+        return true;
+    }
+
+    let (presses_a, presses_b) = buttons.take_presses();
+    // ignore A until released after holding it to open the menu
+    if *armed {
+        (0..presses_a).for_each(|_| *option = option.next());
+    } else {
+        *armed = !buttons.a_held();
+    }
+    (0..presses_b).for_each(|_| option.change(settings));
+    false
+}
+
+/// the option on the left and its value on the right.
+#[inline]
+pub fn draw_settings_menu(option: MenuOption, settings: &Settings, frame: &mut Frame) {
+    frame.set_cols(0, &option.glyph());
+    frame.set_col(VALUE_COL, option.value_glyph(settings));
+}
+
+/// two dots for two players, otherwise a bar as high as the difficulty.
+#[inline]
+pub(super) fn opponent_glyph(bot: Option<Difficulty>) -> u8 {
+    match bot {
+        None => 0b10001,
+        Some(difficulty) => bar(difficulty as u8 + 1),
+    }
+}
+
+/// a bar of `height` rows from the bottom.
+#[inline]
+fn bar(height: u8) -> u8 {
+    ALL_ROWS << (5 - height) & ALL_ROWS
+}
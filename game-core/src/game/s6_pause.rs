@@ -0,0 +1,89 @@
+//! Pause of a round, resumed after a short count down.
+use super::{
+    s1_ready::{draw_ready_animation, DIGIT},
+    GameTimer,
+};
+use crate::{ButtonInput, Chord, Frame, GameClock, Gesture, SoundSink, TimerMode, Track};
+
+/// pauses the round at once, from the logo or the keyboard.
+pub const PAUSE: Gesture = Gesture::LongPress(Chord::AB);
+/// seconds of the count down before the round resumes.
+const RESUME: u8 = 3;
+/// the two bars of the pause icon, from col 1.
+const ICON: [u8; 3] = [0b11111, 0, 0b11111];
+
+/// Check the pause gesture, or the hold of A and B, during a round.
+///
+/// the dot runs until then, and stops with the presses since discarded.
+///
+/// returns true if paused.
+#[inline]
+pub fn pause(
+    gesture: Option<Gesture>,
+    buttons: &mut impl ButtonInput,
+    sound: &mut impl SoundSink,
+    clock: &mut impl GameClock,
+) -> bool {
+    if !matches!(gesture, Some(PAUSE | Gesture::Hold(Chord::AB))) {
+        return false;
+    }
+    clock.cancel_timers();
+    sound.stop();
+    buttons.reset();
+    true
+}
+
+/// Wait for both buttons, then count down.
+///
+/// `held` while A and B are still held from pausing, released
+/// before they count as pressed.
+///
+/// presses while paused do not count.
+///
+/// returns true when the round resumes.
+pub fn paused(
+    gesture: Option<Gesture>,
+    timer: Option<GameTimer>,
+    held: &mut bool,
+    resume: &mut u8,
+    buttons: &mut impl ButtonInput,
+    sound: &mut impl SoundSink,
+    clock: &mut impl GameClock,
+) -> bool {
+    if *held {
+        if let Some(Gesture::Release(Chord::AB) | Gesture::LongPress(Chord::AB)) = gesture {
+            *held = false;
+            buttons.reset();
+        }
+        return false;
+    }
+    if *resume == 0 {
+        if buttons.both_pressed() {
+            buttons.reset();
+            *resume = RESUME;
+            clock.start_timer(GameTimer::Digit, DIGIT, TimerMode::Periodic);
+            sound.play_track(Track::DiLo);
+        }
+        return false;
+    }
+    if timer != Some(GameTimer::Digit) {
+        return false;
+    }
+    *resume -= 1;
+    if *resume > 0 {
+        sound.play_track(Track::DiLo);
+        return false;
+    }
+    buttons.reset();
+    sound.play_track(Track::DiHi);
+    true
+}
+
+/// the pause icon, or the digit of the count down.
+pub fn draw_paused(resume: u8, frame: &mut Frame) {
+    if resume == 0 {
+        frame.set_cols(1, &ICON);
+    } else {
+        draw_ready_animation(resume, frame);
+    }
+}
@@ -0,0 +1,303 @@
+//! Gestures made of the debounced button edges.
+use crate::{Duration, Instant, Players};
+
+/// time to hold a button alone for a long press.
+pub const LONG_PRESS: Duration = Duration::from_secs(1);
+/// longest time between the taps of a double tap.
+pub const DOUBLE_TAP: Duration = Duration::from_ms(300);
+/// time to hold both buttons for a long press of the chord.
+pub const CHORD_HOLD: Duration = Duration::from_secs(3);
+/// time to hold both buttons for a hold of the chord, much longer than
+/// two players tapping fast can overlap.
+pub const HOLD: Duration = Duration::from_secs(1);
+
+/// The buttons making a gesture.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Chord {
+    A,
+    B,
+    /// A and B held down together.
+    AB,
+    /// the touch logo.
+    Logo,
+}
+
+impl From<Players> for Chord {
+    fn from(player: Players) -> Self {
+        match player {
+            Players::A => Chord::A,
+            Players::B => Chord::B,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gesture {
+    /// pressed and released before a long press.
+    Tap(Chord),
+    /// a second tap right after a tap, instead of `Tap`.
+    DoubleTap(Chord),
+    /// held down, reported once while still held.
+    LongPress(Chord),
+    /// A and B held together a second, reported once while still held.
+    Hold(Chord),
+    /// a hold released before its long press, instead of `Tap`.
+    Release(Chord),
+}
+
+impl Gesture {
+    /// the kind in the high bits, the chord in the low 2 bits.
+    pub fn to_u8(self) -> u8 {
+        let (kind, chord) = match self {
+            Gesture::Tap(chord) => (0, chord),
+            Gesture::DoubleTap(chord) => (1, chord),
+            Gesture::LongPress(chord) => (2, chord),
+            Gesture::Hold(chord) => (3, chord),
+            Gesture::Release(chord) => (4, chord),
+        };
+        kind << 2 | chord as u8
+    }
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        let chord = match value & 0b11 {
+            0 => Chord::A,
+            1 => Chord::B,
+            2 => Chord::AB,
+            _ => Chord::Logo,
+        };
+        match value >> 2 {
+            0 => Some(Gesture::Tap(chord)),
+            1 => Some(Gesture::DoubleTap(chord)),
+            2 => Some(Gesture::LongPress(chord)),
+            3 => Some(Gesture::Hold(chord)),
+            4 => Some(Gesture::Release(chord)),
+            _ => None,
+        }
+    }
+}
+
+/// The buttons held since none were.
+#[derive(Clone, Copy, Debug)]
+struct Group {
+    chord: Chord,
+    start: Instant,
+    /// since when the chord is held, A and B pressed together for `AB`.
+    held_since: Instant,
+    /// the hold of the chord is reported.
+    held: bool,
+    /// the long press, or the release of the hold, is reported.
+    done: bool,
+}
+
+/// Classifies the button edges into gestures.
+///
+/// a group of presses starts with a button pressed while none is, and
+/// ends when all are released. It is a chord of A and B if they are
+/// held together at some point.
+///
+/// a chord held a second is a hold, released as soon as A or B is.
+#[derive(Clone, Copy, Debug)]
+pub struct GestureDetector {
+    long_press: Duration,
+    double_tap: Duration,
+    chord_hold: Duration,
+    down: [bool; 2],
+    group: Option<Group>,
+    /// the last tap, and when it ended.
+    last_tap: Option<(Chord, Instant)>,
+}
+
+impl GestureDetector {
+    pub const fn new() -> Self {
+        Self::with_times(LONG_PRESS, DOUBLE_TAP, CHORD_HOLD)
+    }
+
+    pub const fn with_times(
+        long_press: Duration,
+        double_tap: Duration,
+        chord_hold: Duration,
+    ) -> Self {
+        Self {
+            long_press,
+            double_tap,
+            chord_hold,
+            down: [false; 2],
+            group: None,
+            last_tap: None,
+        }
+    }
+
+    /// a debounced edge, `down` for a press.
+    ///
+    /// returns the tap, double tap or end of a hold made by a release.
+    pub fn edge(&mut self, player: Players, down: bool, at: Instant) -> Option<Gesture> {
+        self.down[player as usize] = down;
+        if down {
+            match &mut self.group {
+                None => {
+                    self.group = Some(Group {
+                        chord: player.into(),
+                        start: at,
+                        held_since: at,
+                        held: false,
+                        done: false,
+                    })
+                }
+                Some(group) if self.down == [true; 2] && group.chord != Chord::AB => {
+                    group.chord = Chord::AB;
+                    group.held_since = at;
+                    group.done = false;
+                }
+                Some(_) => (),
+            }
+            return None;
+        }
+        let group = self.group.as_mut()?;
+        if group.held && !group.done {
+            group.done = true;
+            return Some(Gesture::Release(group.chord));
+        }
+        if self.down.contains(&true) {
+            return None;
+        }
+        let group = self.group.take()?;
+        if group.done || at.since(group.start) >= self.long_press {
+            return None;
+        }
+        match self.last_tap.take() {
+            Some((chord, end))
+                if chord == group.chord && group.start.since(end) <= self.double_tap =>
+            {
+                Some(Gesture::DoubleTap(chord))
+            }
+            _ => {
+                self.last_tap = Some((group.chord, at));
+                Some(Gesture::Tap(group.chord))
+            }
+        }
+    }
+
+    /// returns the hold or long press of the held buttons, call each tick.
+    pub fn poll(&mut self, now: Instant) -> Option<Gesture> {
+        let group = self.group.as_mut()?;
+        let (held, hold) = match group.chord {
+            Chord::AB => (self.down == [true; 2], self.chord_hold),
+            _ => (true, self.long_press),
+        };
+        if group.done || !held {
+            return None;
+        }
+        let since = now.since(group.held_since);
+        if group.chord == Chord::AB && !group.held && since >= HOLD {
+            group.held = true;
+            return Some(Gesture::Hold(group.chord));
+        }
+        if since < hold {
+            return None;
+        }
+        group.done = true;
+        Some(Gesture::LongPress(group.chord))
+    }
+}
+
+impl Default for GestureDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_gesture {
+    use super::*;
+
+    /// feed `(tick, player, down)` edges, polling every tick up to `end`.
+    fn run(edges: &[(u32, Players, bool)], end: u32) -> Vec<(u32, Gesture)> {
+        let mut detector = GestureDetector::new();
+        let mut gestures = Vec::new();
+        let mut edges = edges.iter().peekable();
+        for tick in 0..end {
+            let now = Instant::from_ticks(tick);
+            while let Some((_, player, down)) = edges.next_if(|(at, ..)| *at == tick) {
+                gestures.extend(detector.edge(*player, *down, now).map(|g| (tick, g)));
+            }
+            gestures.extend(detector.poll(now).map(|g| (tick, g)));
+        }
+        gestures
+    }
+
+    #[test]
+    fn test_taps() {
+        use Players::{A, B};
+        let edges = [
+            (0, A, true),
+            (10, A, false),
+            // double tap
+            (50, A, true),
+            (60, A, false),
+            // too late for a double tap
+            (200, A, true),
+            (210, A, false),
+            (400, B, true),
+            (410, B, false),
+        ];
+        assert_eq!(
+            run(&edges, 500),
+            [
+                (10, Gesture::Tap(Chord::A)),
+                (60, Gesture::DoubleTap(Chord::A)),
+                (210, Gesture::Tap(Chord::A)),
+                (410, Gesture::Tap(Chord::B)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_press() {
+        use Players::{A, B};
+        let long = LONG_PRESS.ticks();
+        let edges = [
+            (0, A, true),
+            (long + 10, A, false),
+            // no long press once B joins
+            (1000, A, true),
+            (1010, B, true),
+            (1020, B, false),
+            (1000 + long + 10, A, false),
+        ];
+        assert_eq!(run(&edges, 2000), [(long, Gesture::LongPress(Chord::A))]);
+    }
+
+    #[test]
+    fn test_chord() {
+        use Players::{A, B};
+        let hold = CHORD_HOLD.ticks();
+        let moment = HOLD.ticks();
+        let edges = [
+            // pressed together, released apart
+            (0, A, true),
+            (2, B, true),
+            (20, A, false),
+            (30, B, false),
+            // held together
+            (100, B, true),
+            (101, A, true),
+            (101 + hold + 5, A, false),
+            (101 + hold + 6, B, false),
+            // held a while, released apart
+            (1000, A, true),
+            (1001, B, true),
+            (1001 + moment + 50, B, false),
+            (1001 + moment + 60, A, false),
+        ];
+        assert_eq!(
+            run(&edges, 2000),
+            [
+                (30, Gesture::Tap(Chord::AB)),
+                (101 + moment, Gesture::Hold(Chord::AB)),
+                (101 + hold, Gesture::LongPress(Chord::AB)),
+                (1001 + moment, Gesture::Hold(Chord::AB)),
+                (1001 + moment + 50, Gesture::Release(Chord::AB)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Traits abstracting the devices used by the game.
+use crate::{Duration, Frame, GameTimer, Gesture, Instant, Players, TimerMode};
+
+/// Tracks played by the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Track {
+    /// countdown beep.
+    DiLo,
+    /// game start beep.
+    DiHi,
+    /// result song.
+    Peppa,
+    /// match winner song.
+    Fanfare,
+    /// false start buzzer.
+    Buzz,
+}
+
+/// Events pushed by the interrupts, handled in order by the game tick.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputEvent {
+    /// a button of the team of `player` went down at `at`,
+    /// `member` 0 is the button on the board.
+    ButtonDown {
+        player: Players,
+        member: u8,
+        at: Instant,
+    },
+    /// a button of the team of `player` went up at `at`.
+    ButtonUp {
+        player: Players,
+        member: u8,
+        at: Instant,
+    },
+    /// the touch logo is touched, or released, at `at`.
+    Logo { touched: bool, at: Instant },
+    /// the track finished playing.
+    TrackFinished(Track),
+}
+
+impl InputEvent {
+    /// when the event happened, unknown for a finished track.
+    #[inline]
+    pub fn at(&self) -> Option<Instant> {
+        match *self {
+            InputEvent::ButtonDown { at, .. }
+            | InputEvent::ButtonUp { at, .. }
+            | InputEvent::Logo { at, .. } => Some(at),
+            InputEvent::TrackFinished(_) => None,
+        }
+    }
+}
+
+/// The 5x5 LED matrix, multiplexed by the driver.
+pub trait LedMatrix {
+    /// show the frame, replacing the previous one.
+    fn show(&mut self, frame: &Frame);
+}
+
+/// Button presses collected between game ticks.
+pub trait ButtonInput {
+    /// both A and B have been pressed together.
+    fn both_pressed(&self) -> bool;
+
+    /// the last pressed button is A.
+    fn last_a(&self) -> bool;
+
+    /// forget all previous presses.
+    fn reset(&mut self);
+
+    /// mark A as the last pressed button.
+    fn set_last_a(&mut self);
+
+    /// presses of A and B since the last call.
+    fn take_presses(&mut self) -> (u8, u8);
+
+    /// A is currently held down.
+    fn a_held(&self) -> bool;
+
+    /// the last long press or double tap since the last call.
+    fn take_gesture(&mut self) -> Option<Gesture>;
+}
+
+/// Sound output.
+pub trait SoundSink {
+    /// set track and start playing.
+    /// If currently playing, stop this track.
+    fn play_track(&mut self, track: Track);
+
+    /// stop playing, if playing.
+    fn stop(&mut self);
+}
+
+/// Source of random numbers.
+pub trait RandomSource {
+    fn random_u8(&mut self) -> u8;
+}
+
+/// The 256HZ tick driving the game.
+pub trait TickAck {
+    /// acknowledge the current tick event.
+    fn ack_tick(&mut self);
+}
+
+/// The clock of the game ticks, running the timers of the game states.
+pub trait GameClock: TickAck {
+    /// fire `timer` after `duration`, and every `duration` if periodic,
+    /// restarting it if running.
+    fn start_timer(&mut self, timer: GameTimer, duration: Duration, mode: TimerMode);
+
+    /// stop the timers of the state left.
+    fn cancel_timers(&mut self);
+
+    /// take a timer due at the current tick, if any.
+    fn poll_timer(&mut self) -> Option<GameTimer>;
+
+    /// time until `timer` fires, if running.
+    fn remaining(&self, timer: GameTimer) -> Option<Duration>;
+}
+
+/// Devices used for the game.
+pub struct Device<B, M, S, R> {
+    pub buttons: B,
+    pub display: M,
+    pub sound: S,
+    pub rng: R,
+}
@@ -0,0 +1,47 @@
+//! Hardware independent logic of the tug of war game.
+//!
+//! The game state machine only talks to the hardware through the traits
+//! in [`hal`], so that it can be driven by the micro:bit firmware as well
+//! as by host side tests and tools.
+#![cfg_attr(not(test), no_std)]
+
+mod animation;
+mod bot;
+pub mod broadcast;
+mod buttons;
+pub mod console;
+mod debounce;
+#[cfg(test)]
+mod fake;
+mod font;
+mod frame;
+pub mod game;
+mod gesture;
+pub mod hal;
+pub mod link;
+mod queue;
+pub mod replay;
+mod score;
+mod scroller;
+mod settings;
+mod spiral;
+pub mod store;
+pub mod telemetry;
+mod timer;
+mod touch;
+pub use animation::*;
+pub use bot::*;
+pub use buttons::*;
+pub use debounce::*;
+pub use font::*;
+pub use frame::*;
+pub use game::*;
+pub use gesture::*;
+pub use hal::*;
+pub use queue::*;
+pub use score::*;
+pub use scroller::*;
+pub use settings::*;
+pub use spiral::*;
+pub use timer::*;
+pub use touch::*;
@@ -0,0 +1,767 @@
+//! Link between the board running the game and the remote boards of
+//! the players, over a lossy transport such as the radio.
+//!
+//! A [`Remote`] asks the [`Host`] for a player until it is paired, then
+//! sends the debounced edges of its button, numbered in sequence. The
+//! host acknowledges the next edge it expects, and the remote sends the
+//! edges not acknowledged again. The host delivers the edges of each
+//! remote in order, stamped with the time they happened: each edge
+//! carries how long it waited on the remote, and the latency estimated
+//! by the remote from the round trips of its edges.
+//!
+//! Frame format: the version byte, the kind, the id of the remote in
+//! 4 bytes little endian, then the fields of the kind, little endian:
+//! - pair: none.
+//! - paired: the player.
+//! - edge: the sequence number in 2 bytes, 1 if down, the age in
+//!   2 bytes and the latency, both in ticks.
+//! - ack: the next sequence number expected, and the one of the edge
+//!   acknowledged, in 2 bytes each.
+use crate::{Duration, InputEvent, Instant, Players, TEAM_SIZE};
+
+pub const VERSION: u8 = 1;
+/// bytes of the longest frame.
+pub const MAX_FRAME: usize = 12;
+const KIND_PAIR: u8 = 0;
+const KIND_PAIRED: u8 = 1;
+const KIND_EDGE: u8 = 2;
+const KIND_ACK: u8 = 3;
+/// edges sent ahead of the acknowledged ones.
+pub const WINDOW: usize = 8;
+/// least time before an edge not acknowledged is sent again, twice the
+/// round trip time when longer.
+pub const RESEND: Duration = Duration::from_ms(30);
+/// time between the pairing requests of a remote.
+pub const PAIR_INTERVAL: Duration = Duration::from_ms(250);
+/// a remote pairs again after hearing nothing from the host for this
+/// long, while it has edges to send.
+pub const LINK_TIMEOUT: Duration = Duration::from_secs(2);
+/// the button of the team pressed by a remote.
+pub const REMOTE_MEMBER: u8 = TEAM_SIZE as u8 - 1;
+/// round trip time assumed before the first one, in ticks.
+const INITIAL_RTT: u32 = 2;
+/// bits of fraction of the smoothed round trip time.
+const RTT_SHIFT: u32 = 3;
+/// the time before sending again doubles up to 4 times its value.
+const MAX_BACKOFF: u8 = 2;
+
+/// Frames sent to the other boards.
+pub trait Transport {
+    /// send a frame, which may be lost, or arrive after later ones.
+    fn send(&mut self, frame: &[u8]);
+    /// a frame received, copied into `buf`, returns its length.
+    fn recv(&mut self, buf: &mut [u8; MAX_FRAME]) -> Option<usize>;
+}
+
+/// A frame of the link.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Packet {
+    /// the remote `id` asks for a player.
+    Pair { id: u32 },
+    /// the host gives `player` to the remote `id`.
+    Paired { id: u32, player: Players },
+    /// the button of the remote went down or up, `age` ticks ago,
+    /// plus the one way `latency`.
+    Edge {
+        id: u32,
+        seq: u16,
+        down: bool,
+        age: u16,
+        latency: u8,
+    },
+    /// the host received edge `got` of the remote, and expects edge
+    /// `seq` next.
+    Ack { id: u32, seq: u16, got: u16 },
+}
+
+/// Malformed frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LinkError {
+    /// frame of another version of the protocol.
+    Version(u8),
+    /// unknown kind of packet.
+    Kind(u8),
+    /// frame too short for its kind, or a field out of range.
+    Field,
+}
+
+impl Packet {
+    /// the remote sending or receiving the packet.
+    pub fn id(&self) -> u32 {
+        match self {
+            Packet::Pair { id }
+            | Packet::Paired { id, .. }
+            | Packet::Edge { id, .. }
+            | Packet::Ack { id, .. } => *id,
+        }
+    }
+
+    /// returns the length of the frame written to `buf`.
+    pub fn encode(&self, buf: &mut [u8; MAX_FRAME]) -> usize {
+        buf[0] = VERSION;
+        buf[2..6].copy_from_slice(&self.id().to_le_bytes());
+        let (kind, len) = match *self {
+            Packet::Pair { .. } => (KIND_PAIR, 6),
+            Packet::Paired { player, .. } => {
+                buf[6] = player as u8;
+                (KIND_PAIRED, 7)
+            }
+            Packet::Edge {
+                seq,
+                down,
+                age,
+                latency,
+                ..
+            } => {
+                buf[6..8].copy_from_slice(&seq.to_le_bytes());
+                buf[8] = down as u8;
+                buf[9..11].copy_from_slice(&age.to_le_bytes());
+                buf[11] = latency;
+                (KIND_EDGE, 12)
+            }
+            Packet::Ack { seq, got, .. } => {
+                buf[6..8].copy_from_slice(&seq.to_le_bytes());
+                buf[8..10].copy_from_slice(&got.to_le_bytes());
+                (KIND_ACK, 10)
+            }
+        };
+        buf[1] = kind;
+        len
+    }
+
+    pub fn decode(frame: &[u8]) -> Result<Self, LinkError> {
+        let (kind, id, fields) = match frame {
+            [VERSION, kind, i0, i1, i2, i3, fields @ ..] => {
+                (*kind, u32::from_le_bytes([*i0, *i1, *i2, *i3]), fields)
+            }
+            [version, ..] if *version != VERSION => return Err(LinkError::Version(*version)),
+            _ => return Err(LinkError::Field),
+        };
+        match (kind, fields) {
+            (KIND_PAIR, []) => Ok(Packet::Pair { id }),
+            (KIND_PAIRED, [player]) => Ok(Packet::Paired {
+                id,
+                player: match player {
+                    0 => Players::A,
+                    1 => Players::B,
+                    _ => return Err(LinkError::Field),
+                },
+            }),
+            (KIND_EDGE, [s0, s1, down @ 0..=1, a0, a1, latency]) => Ok(Packet::Edge {
+                id,
+                seq: u16::from_le_bytes([*s0, *s1]),
+                down: *down == 1,
+                age: u16::from_le_bytes([*a0, *a1]),
+                latency: *latency,
+            }),
+            (KIND_ACK, [s0, s1, g0, g1]) => Ok(Packet::Ack {
+                id,
+                seq: u16::from_le_bytes([*s0, *s1]),
+                got: u16::from_le_bytes([*g0, *g1]),
+            }),
+            (KIND_PAIR..=KIND_ACK, _) => Err(LinkError::Field),
+            (kind, _) => Err(LinkError::Kind(kind)),
+        }
+    }
+}
+
+/// sequence number `a` comes before `b`.
+#[inline]
+fn before(a: u16, b: u16) -> bool {
+    (a.wrapping_sub(b) as i16) < 0
+}
+
+#[inline]
+fn send<T: Transport>(transport: &mut T, packet: Packet) {
+    let mut buf = [0; MAX_FRAME];
+    let len = packet.encode(&mut buf);
+    transport.send(&buf[..len]);
+}
+
+/// An edge waiting for its acknowledgement.
+#[derive(Clone, Copy, Debug)]
+struct Pending {
+    seq: u16,
+    down: bool,
+    /// when the edge happened.
+    at: Instant,
+    /// when it was last sent.
+    sent: Instant,
+    /// sent more than once, so its acknowledgement does not time the
+    /// round trip, as the acknowledgement of a later edge would not.
+    resent: bool,
+}
+
+/// The board of a player, sending the edges of its button to the host.
+pub struct Remote<T> {
+    transport: T,
+    id: u32,
+    player: Option<Players>,
+    /// sequence number of the next edge.
+    seq: u16,
+    /// edges not acknowledged, by sequence number modulo `WINDOW`.
+    pending: [Option<Pending>; WINDOW],
+    /// smoothed round trip time, in ticks with `RTT_SHIFT` bits of
+    /// fraction.
+    rtt: u32,
+    /// doublings of the time before sending again, since the last
+    /// round trip timed.
+    backoff: u8,
+    /// the last pairing request, or the last frame of the host, or
+    /// the edge sent when none was waiting.
+    heard: Option<Instant>,
+}
+
+impl<T: Transport> Remote<T> {
+    /// `id` tells the remotes apart, such as the id of the chip.
+    pub fn new(transport: T, id: u32) -> Self {
+        Self {
+            transport,
+            id,
+            player: None,
+            seq: 0,
+            pending: [None; WINDOW],
+            rtt: INITIAL_RTT << RTT_SHIFT,
+            backoff: 0,
+            heard: None,
+        }
+    }
+
+    /// the player given by the host, once paired.
+    #[inline]
+    pub fn player(&self) -> Option<Players> {
+        self.player
+    }
+
+    /// the estimated one way latency to the host.
+    #[inline]
+    pub fn latency(&self) -> Duration {
+        Duration::from_ticks(self.rtt >> (RTT_SHIFT + 1))
+    }
+
+    #[inline]
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// time before an edge is sent again.
+    fn resend(&self) -> Duration {
+        let rtt = Duration::from_ticks(self.rtt >> (RTT_SHIFT - 1));
+        Duration::from_ticks(rtt.max(RESEND).ticks() << self.backoff)
+    }
+
+    /// the debounced button went down (`down`) or up at `at`.
+    ///
+    /// returns false if dropped, when not paired or `WINDOW` edges
+    /// wait for their acknowledgement.
+    pub fn edge(&mut self, down: bool, at: Instant) -> bool {
+        let slot = &mut self.pending[self.seq as usize % WINDOW];
+        if self.player.is_none() || slot.is_some() {
+            return false;
+        }
+        if self.pending.iter().all(Option::is_none) {
+            self.heard = Some(at);
+        }
+        let slot = &mut self.pending[self.seq as usize % WINDOW];
+        *slot = Some(Pending {
+            seq: self.seq,
+            down,
+            at,
+            sent: at,
+            resent: false,
+        });
+        self.send_edge(self.seq as usize % WINDOW, at);
+        self.seq = self.seq.wrapping_add(1);
+        true
+    }
+
+    /// receive the frames of the host, and send the pairing request or
+    /// the edges again when due, call each tick.
+    pub fn poll(&mut self, now: Instant) {
+        let mut buf = [0; MAX_FRAME];
+        while let Some(len) = self.transport.recv(&mut buf) {
+            match Packet::decode(&buf[..len]) {
+                Ok(Packet::Paired { id, player }) if id == self.id && self.player.is_none() => {
+                    self.player = Some(player);
+                    self.heard = Some(now);
+                }
+                Ok(Packet::Ack { id, seq, got }) if id == self.id && self.player.is_some() => {
+                    self.ack(seq, got, now)
+                }
+                _ => (),
+            }
+        }
+        let heard = self.heard.map(|heard| now.since(heard));
+        if self.player.is_none() {
+            if heard.is_none_or(|heard| heard >= PAIR_INTERVAL) {
+                self.heard = Some(now);
+                send(&mut self.transport, Packet::Pair { id: self.id });
+            }
+            return;
+        }
+        if heard.is_some_and(|heard| heard >= LINK_TIMEOUT)
+            && self.pending.iter().any(Option::is_some)
+        {
+            // the host forgot the remote, e.g. restarted
+            self.unpair();
+            return;
+        }
+        let resend = self.resend();
+        let mut resent = false;
+        for index in 0..WINDOW {
+            match &mut self.pending[index] {
+                Some(pending) if now.since(pending.sent) >= resend => {
+                    pending.resent = true;
+                    resent = true;
+                    self.send_edge(index, now);
+                }
+                _ => (),
+            }
+        }
+        if resent {
+            self.backoff = (self.backoff + 1).min(MAX_BACKOFF);
+        }
+    }
+
+    /// ask for a player again.
+    pub fn unpair(&mut self) {
+        self.player = None;
+        self.seq = 0;
+        self.pending = [None; WINDOW];
+        self.backoff = 0;
+        self.heard = None;
+    }
+
+    fn send_edge(&mut self, index: usize, now: Instant) {
+        let Some(pending) = &mut self.pending[index] else {
+            return;
+        };
+        pending.sent = now;
+        let packet = Packet::Edge {
+            id: self.id,
+            seq: pending.seq,
+            down: pending.down,
+            age: now.since(pending.at).ticks().min(u16::MAX as u32) as u16,
+            latency: self.latency().ticks().min(u8::MAX as u32) as u8,
+        };
+        send(&mut self.transport, packet);
+    }
+
+    /// the host received edge `got`, and the edges before `seq`.
+    fn ack(&mut self, seq: u16, got: u16, now: Instant) {
+        self.heard = Some(now);
+        match self.pending[got as usize % WINDOW] {
+            Some(pending) if pending.seq == got && !pending.resent => {
+                let sample = now.since(pending.sent).ticks();
+                self.rtt = self.rtt - (self.rtt >> RTT_SHIFT) + sample;
+                self.backoff = 0;
+            }
+            _ => (),
+        }
+        for slot in &mut self.pending {
+            if slot.is_some_and(|pending| before(pending.seq, seq)) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// A remote paired with the host.
+#[derive(Clone, Copy, Debug)]
+struct Peer {
+    id: u32,
+    /// sequence number of the next edge to deliver.
+    next: u16,
+    /// edges received ahead of `next`, by sequence number modulo
+    /// `WINDOW`.
+    received: [Option<(u16, bool, Instant)>; WINDOW],
+    /// time of the last edge delivered, edges are not stamped before it.
+    last: Option<Instant>,
+}
+
+impl Peer {
+    fn new(id: u32) -> Self {
+        Self {
+            id,
+            next: 0,
+            received: [None; WINDOW],
+            last: None,
+        }
+    }
+
+    /// the sequence number after the edges received in a row.
+    fn ack(&self) -> u16 {
+        let mut seq = self.next;
+        while matches!(self.received[seq as usize % WINDOW], Some((s, ..)) if s == seq) {
+            seq = seq.wrapping_add(1);
+        }
+        seq
+    }
+}
+
+/// The board running the game, receiving the edges of the remotes.
+pub struct Host<T> {
+    transport: T,
+    /// the remotes of A and B.
+    peers: [Option<Peer>; 2],
+}
+
+impl<T: Transport> Host<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            peers: [None; 2],
+        }
+    }
+
+    /// a remote plays for `player`.
+    #[inline]
+    pub fn is_paired(&self, player: Players) -> bool {
+        self.peers[player as usize].is_some()
+    }
+
+    #[inline]
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// forget the remotes, they pair again once their link times out.
+    pub fn unpair(&mut self) {
+        self.peers = [None; 2];
+    }
+
+    /// receive the frames of the remotes, received at `now`.
+    ///
+    /// returns the edges of the remotes as button events, in order,
+    /// call until it returns `None`.
+    pub fn poll(&mut self, now: Instant) -> Option<InputEvent> {
+        let mut buf = [0; MAX_FRAME];
+        loop {
+            if let Some(event) = self.deliver(now) {
+                return Some(event);
+            }
+            let len = self.transport.recv(&mut buf)?;
+            match Packet::decode(&buf[..len]) {
+                Ok(Packet::Pair { id }) => self.pair(id),
+                Ok(Packet::Edge {
+                    id,
+                    seq,
+                    down,
+                    age,
+                    latency,
+                }) => {
+                    let at = now.before(Duration::from_ticks(age as u32 + latency as u32));
+                    self.receive(id, seq, down, at);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// the first free player for a new remote, the same for a remote
+    /// pairing again.
+    fn pair(&mut self, id: u32) {
+        let slot = match self
+            .peers
+            .iter()
+            .position(|p| p.is_some_and(|p| p.id == id))
+        {
+            Some(slot) => slot,
+            None => match self.peers.iter().position(Option::is_none) {
+                Some(slot) => slot,
+                None => return,
+            },
+        };
+        self.peers[slot] = Some(Peer::new(id));
+        let player = if slot == 0 { Players::A } else { Players::B };
+        send(&mut self.transport, Packet::Paired { id, player });
+    }
+
+    fn receive(&mut self, id: u32, seq: u16, down: bool, at: Instant) {
+        let Some(peer) = self.peers.iter_mut().flatten().find(|p| p.id == id) else {
+            return;
+        };
+        // edges already delivered are acknowledged again
+        if (seq.wrapping_sub(peer.next) as usize) < WINDOW {
+            peer.received[seq as usize % WINDOW] = Some((seq, down, at));
+        }
+        let packet = Packet::Ack {
+            id,
+            seq: peer.ack(),
+            got: seq,
+        };
+        send(&mut self.transport, packet);
+    }
+
+    /// the next edge in order of a remote.
+    fn deliver(&mut self, now: Instant) -> Option<InputEvent> {
+        self.peers.iter_mut().enumerate().find_map(|(slot, peer)| {
+            let peer = peer.as_mut()?;
+            let slot_next = &mut peer.received[peer.next as usize % WINDOW];
+            let (_, down, mut at) = slot_next.take_if(|(seq, ..)| *seq == peer.next)?;
+            peer.next = peer.next.wrapping_add(1);
+            if let Some(last) = peer.last {
+                if now.since(at) > now.since(last) {
+                    at = last;
+                }
+            }
+            peer.last = Some(at);
+            let player = if slot == 0 { Players::A } else { Players::B };
+            let member = REMOTE_MEMBER;
+            Some(if down {
+                InputEvent::ButtonDown { player, member, at }
+            } else {
+                InputEvent::ButtonUp { player, member, at }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_link {
+    use super::*;
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+    /// Frames in flight between the host, side 0, and the remotes.
+    struct Air {
+        tick: u32,
+        /// frames of each side, and the tick they arrive.
+        inbox: Vec<VecDeque<(u32, Vec<u8>)>>,
+        /// percent of the frames lost.
+        loss: u32,
+        delay: u32,
+        /// most extra delay, which reorders the frames.
+        jitter: u32,
+        seed: u32,
+    }
+
+    impl Air {
+        fn new(sides: usize, loss: u32, delay: u32, jitter: u32) -> Rc<RefCell<Self>> {
+            Rc::new(RefCell::new(Self {
+                tick: 0,
+                inbox: vec![VecDeque::new(); sides],
+                loss,
+                delay,
+                jitter,
+                seed: 0x1234_5678,
+            }))
+        }
+
+        fn random(&mut self, below: u32) -> u32 {
+            // xorshift
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 17;
+            self.seed ^= self.seed << 5;
+            self.seed % below.max(1)
+        }
+    }
+
+    /// An end of the loopback, broadcasting like the radio.
+    struct Loopback {
+        air: Rc<RefCell<Air>>,
+        side: usize,
+    }
+
+    impl Transport for Loopback {
+        fn send(&mut self, frame: &[u8]) {
+            let mut air = self.air.borrow_mut();
+            for side in 0..air.inbox.len() {
+                // the remotes only hear the host
+                if side == self.side || (self.side != 0 && side != 0) {
+                    continue;
+                }
+                if air.random(100) < air.loss {
+                    continue;
+                }
+                let jitter = air.jitter + 1;
+                let at = air.tick + air.delay + air.random(jitter);
+                air.inbox[side].push_back((at, frame.to_vec()));
+            }
+        }
+
+        fn recv(&mut self, buf: &mut [u8; MAX_FRAME]) -> Option<usize> {
+            let mut air = self.air.borrow_mut();
+            let tick = air.tick;
+            let inbox = &mut air.inbox[self.side];
+            let index = inbox.iter().position(|(at, _)| *at <= tick)?;
+            let (_, frame) = inbox.remove(index)?;
+            buf[..frame.len()].copy_from_slice(&frame);
+            Some(frame.len())
+        }
+    }
+
+    struct Boards {
+        air: Rc<RefCell<Air>>,
+        host: Host<Loopback>,
+        remotes: Vec<Remote<Loopback>>,
+        events: Vec<InputEvent>,
+    }
+
+    impl Boards {
+        fn new(remotes: usize, loss: u32, delay: u32, jitter: u32) -> Self {
+            let air = Air::new(remotes + 1, loss, delay, jitter);
+            let loopback = |side| Loopback {
+                air: air.clone(),
+                side,
+            };
+            Self {
+                host: Host::new(loopback(0)),
+                remotes: (1..=remotes)
+                    .map(|side| Remote::new(loopback(side), 100 + side as u32))
+                    .collect(),
+                events: Vec::new(),
+                air,
+            }
+        }
+
+        fn now(&self) -> Instant {
+            Instant::from_ticks(self.air.borrow().tick)
+        }
+
+        fn run(&mut self, ticks: u32) {
+            for _ in 0..ticks {
+                let now = self.now();
+                self.remotes.iter_mut().for_each(|r| r.poll(now));
+                while let Some(event) = self.host.poll(now) {
+                    self.events.push(event);
+                }
+                self.air.borrow_mut().tick += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_packets() {
+        let packets = [
+            Packet::Pair { id: 7 },
+            Packet::Paired {
+                id: 7,
+                player: Players::B,
+            },
+            Packet::Edge {
+                id: u32::MAX,
+                seq: 0xabcd,
+                down: true,
+                age: 300,
+                latency: 3,
+            },
+            Packet::Ack {
+                id: 1,
+                seq: 2,
+                got: 1,
+            },
+        ];
+        for packet in packets {
+            let mut buf = [0; MAX_FRAME];
+            let len = packet.encode(&mut buf);
+            assert_eq!(Packet::decode(&buf[..len]), Ok(packet));
+            assert_eq!(Packet::decode(&buf[..len - 1]), Err(LinkError::Field));
+        }
+        assert_eq!(Packet::decode(&[2, 0]), Err(LinkError::Version(2)));
+        assert_eq!(
+            Packet::decode(&[VERSION, 9, 0, 0, 0, 0]),
+            Err(LinkError::Kind(9))
+        );
+        assert_eq!(
+            Packet::decode(&[VERSION, KIND_PAIRED, 0, 0, 0, 0, 2]),
+            Err(LinkError::Field)
+        );
+        assert!(before(0xffff, 0) && !before(0, 0xffff));
+    }
+
+    #[test]
+    fn test_pairing() {
+        let mut boards = Boards::new(3, 0, 1, 0);
+        boards.run(4);
+        let players: Vec<_> = boards.remotes.iter().map(|r| r.player()).collect();
+        assert_eq!(players, [Some(Players::A), Some(Players::B), None]);
+        assert!(boards.host.is_paired(Players::B));
+        // the third remote keeps asking
+        boards.run(PAIR_INTERVAL.ticks() * 2);
+        assert_eq!(boards.remotes[2].player(), None);
+        // a remote restarting keeps its player
+        boards.remotes[1].unpair();
+        boards.run(4);
+        assert_eq!(boards.remotes[1].player(), Some(Players::B));
+        // the host restarting, the remote pairs again once its edge
+        // is not acknowledged
+        boards.host.unpair();
+        let now = boards.now();
+        assert!(boards.remotes[0].edge(true, now));
+        boards.run(LINK_TIMEOUT.ticks() - 1);
+        assert!(boards.remotes[0].player().is_some());
+        boards.run(PAIR_INTERVAL.ticks() + 4);
+        assert!(boards.host.is_paired(Players::A) && boards.host.is_paired(Players::B));
+        // the third remote took the first free player
+        assert!(boards.remotes[0].player().is_some() && boards.remotes[2].player().is_some());
+    }
+
+    #[test]
+    fn test_loss_and_reordering() {
+        let mut boards = Boards::new(2, 30, 1, 6);
+        boards.run(PAIR_INTERVAL.ticks() * 8);
+        assert!(boards.remotes.iter().all(|r| r.player().is_some()));
+        let player = boards.remotes[0].player().unwrap();
+        let mut sent = 0;
+        while sent < 200 {
+            let now = boards.now();
+            if boards.remotes[0].edge(sent % 2 == 0, now) {
+                sent += 1;
+            }
+            boards.run(1);
+        }
+        boards.run(512);
+        let downs: Vec<_> = boards
+            .events
+            .iter()
+            .map(|event| match *event {
+                InputEvent::ButtonDown { player: p, .. } if p == player => true,
+                InputEvent::ButtonUp { player: p, .. } if p == player => false,
+                _ => panic!("unexpected {event:?}"),
+            })
+            .collect();
+        // each edge once, in order
+        assert_eq!(downs.len(), 200);
+        assert!(downs
+            .iter()
+            .enumerate()
+            .all(|(i, down)| *down == (i % 2 == 0)));
+    }
+
+    #[test]
+    fn test_latency_compensation() {
+        let delay = 5;
+        let mut boards = Boards::new(1, 0, delay, 0);
+        boards.run(PAIR_INTERVAL.ticks());
+        let mut stamps = Vec::new();
+        for i in 0..40 {
+            let at = boards.now();
+            assert!(boards.remotes[0].edge(i % 2 == 0, at));
+            boards.run(16);
+            let Some(InputEvent::ButtonDown { at: got, .. } | InputEvent::ButtonUp { at: got, .. }) =
+                boards.events.pop()
+            else {
+                panic!("edge {i} not delivered");
+            };
+            stamps.push(got.since(at).ticks());
+        }
+        // a round trip of 2 delays, the latency is learnt
+        assert_eq!(boards.remotes[0].latency(), Duration::from_ticks(delay));
+        assert_eq!(stamps[stamps.len() - 1], 0);
+        assert!(stamps[0] > 0);
+        // stamped at the edge, not at the time it was received
+        let mut boards = Boards::new(1, 0, delay, 0);
+        boards.run(PAIR_INTERVAL.ticks());
+        let at = boards.now();
+        boards.run(3);
+        assert!(boards.remotes[0].edge(true, at));
+        boards.run(16);
+        assert!(matches!(
+            boards.events.last(),
+            Some(InputEvent::ButtonDown {
+                member: REMOTE_MEMBER,
+                ..
+            })
+        ));
+    }
+}
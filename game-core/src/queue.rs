@@ -0,0 +1,168 @@
+//! A fixed size queue passing events from interrupts to the game tick.
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU16, AtomicUsize, Ordering},
+};
+
+/// Single producer, single consumer queue of `N` events,
+/// `N` a power of two.
+///
+/// the producer and the consumer may interrupt each other, but there
+/// must be only one of each at a time, e.g. producers running with
+/// interrupts disabled.
+pub struct EventQueue<T, const N: usize> {
+    events: [UnsafeCell<MaybeUninit<T>>; N],
+    /// events pushed, only written by the producer.
+    tail: AtomicUsize,
+    /// events popped, only written by the consumer.
+    head: AtomicUsize,
+    /// events dropped while full.
+    overflows: AtomicU16,
+}
+
+// events are only shared through `push` and `pop`.
+unsafe impl<T: Send, const N: usize> Sync for EventQueue<T, N> {}
+
+impl<T: Copy, const N: usize> EventQueue<T, N> {
+    pub const fn new() -> Self {
+        assert!(N.is_power_of_two());
+        Self {
+            events: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            tail: AtomicUsize::new(0),
+            head: AtomicUsize::new(0),
+            overflows: AtomicU16::new(0),
+        }
+    }
+
+    /// add an event, from the producer.
+    ///
+    /// returns false, and counts an overflow, if full.
+    pub fn push(&self, event: T) -> bool {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.head.load(Ordering::Acquire)) == N {
+            self.overflows.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        // the consumer does not read the slot until the tail moves past it
+        unsafe { (*self.events[tail % N].get()).write(event) };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// take the oldest event, from the consumer.
+    pub fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // the producer does not write the slot until the head moves past it
+        let event = unsafe { (*self.events[head % N].get()).assume_init() };
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(event)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        self.tail.load(Ordering::Acquire).wrapping_sub(head)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// take the events queued so far, at most `N`, ordered by `key`,
+    /// the ones with equal keys in the order they were queued.
+    pub fn drain_by_key<K: Ord>(&self, key: impl Fn(&T) -> K, mut f: impl FnMut(T)) {
+        let mut events = [None; N];
+        let mut len = 0;
+        while len < N {
+            let Some(event) = self.pop() else {
+                break;
+            };
+            let at = events[..len]
+                .iter()
+                .rposition(|queued| queued.as_ref().is_some_and(|q| key(q) <= key(&event)))
+                .map_or(0, |index| index + 1);
+            events[at..=len].rotate_right(1);
+            events[at] = Some(event);
+            len += 1;
+        }
+        events.into_iter().flatten().for_each(&mut f);
+    }
+
+    /// events dropped since started, wrapping around.
+    #[inline]
+    pub fn overflows(&self) -> u16 {
+        self.overflows.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Copy, const N: usize> Default for EventQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_queue {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn test_fifo_and_overflow() {
+        let queue = EventQueue::<u8, 4>::new();
+        assert_eq!(queue.pop(), None);
+        for round in 0..3u8 {
+            for i in 0..6 {
+                queue.push(round * 10 + i);
+            }
+            assert_eq!(queue.len(), 4);
+            let events: Vec<_> = core::iter::from_fn(|| queue.pop()).collect();
+            assert_eq!(events, [0, 1, 2, 3].map(|i| round * 10 + i));
+            assert!(queue.is_empty());
+        }
+        assert_eq!(queue.overflows(), 6);
+    }
+
+    #[test]
+    fn test_drain_by_key() {
+        let queue = EventQueue::<(u8, char), 8>::new();
+        for event in [(3, 'a'), (1, 'b'), (3, 'c'), (0, 'd'), (1, 'e')] {
+            queue.push(event);
+        }
+        let mut events = Vec::new();
+        queue.drain_by_key(|(key, _)| *key, |(_, name)| events.push(name));
+        assert_eq!(events, ['d', 'b', 'e', 'a', 'c']);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_concurrent() {
+        const EVENTS: u32 = 10_000;
+        let queue = Arc::new(EventQueue::<u32, 8>::new());
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for event in 0..EVENTS {
+                    while !queue.push(event) {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+        let mut next = 0;
+        while next < EVENTS {
+            if let Some(event) = queue.pop() {
+                assert_eq!(event, next);
+                next += 1;
+            } else {
+                thread::yield_now();
+            }
+        }
+        producer.join().unwrap();
+        assert!(queue.is_empty());
+    }
+}
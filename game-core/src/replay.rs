@@ -0,0 +1,838 @@
+//! Record and replay of a round.
+//!
+//! A round is fully determined by the button presses and gestures, the
+//! tick they arrive at, and the random byte drawn when the round starts.
+//! [`Recorder`] logs these from the start of the countdown,
+//! and [`Replayer`] feeds them back through [`Game::poll`], until the
+//! round is over, or has not finished long after the log ends.
+//!
+//! Log format: `b"TOW"`, a version byte, the [`Rule`], the
+//! [`FalseStart`] option, the [`Speed`] and the countdown of the round,
+//! followed by records.
+//! Each record starts with a byte `delta << 3 | kind`, where `delta`
+//! is the ticks elapsed since the previous record. A `delta` of 31 or
+//! more is stored as 31, followed by `delta - 31` in LEB128.
+//! Random records are followed by the random byte, and gesture records
+//! by the gesture.
+use crate::{
+    ButtonLatch, Device, DotState, FalseStart, Frame, Game, Gesture, LedMatrix, Players,
+    RandomSource, Rule, Settings, SoundSink, Speed, TickClock, Track, MAX_COUNTDOWN, SPIRAL_LEN,
+    TICK_HZ,
+};
+
+const MAGIC: [u8; 3] = *b"TOW";
+const VERSION: u8 = 5;
+const HEADER_LEN: usize = 8;
+const KIND_MASK: u8 = 0b111;
+const KIND_B: u8 = 0b001;
+const KIND_OTHER_HELD: u8 = 0b010;
+const KIND_RANDOM: u8 = 0b100;
+const KIND_GESTURE: u8 = 0b101;
+const DELTA_SHIFT: u8 = 3;
+const DELTA_EXTENDED: u32 = 31;
+/// kind byte + 5 bytes of LEB128 + random or gesture byte.
+const MAX_RECORD_LEN: usize = 7;
+/// ticks a replayed round goes on without input: a false start, the longest
+/// countdown, and the dot running the whole spiral at the slowest speed.
+pub const END_TICKS: u32 =
+    (MAX_COUNTDOWN as u32 + 2) * TICK_HZ + SPIRAL_LEN as u32 * Speed::Slow.step().ticks();
+
+/// An input to the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Record {
+    /// a button is pressed, `other_held` if the other button is held down.
+    Press {
+        tick: u32,
+        player: Players,
+        other_held: bool,
+    },
+    /// byte drawn from the random source.
+    Random { tick: u32, value: u8 },
+    /// a gesture other than a single tap.
+    Gesture { tick: u32, gesture: Gesture },
+}
+
+impl Record {
+    /// ticks since the start of the round.
+    #[inline]
+    pub fn tick(&self) -> u32 {
+        match self {
+            Record::Press { tick, .. }
+            | Record::Random { tick, .. }
+            | Record::Gesture { tick, .. } => *tick,
+        }
+    }
+}
+
+/// Malformed log.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogError {
+    /// missing magic, unknown version or settings.
+    Header,
+    /// log ends in the middle of a record.
+    Truncated,
+    /// unknown record kind.
+    Kind(u8),
+    /// the game draws a random byte that is not in the log.
+    MissingRandom,
+}
+
+/// Records a round into a fixed size buffer.
+pub struct Recorder<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    tick: u32,
+    last_tick: u32,
+    overflow: bool,
+}
+
+impl<const N: usize> Recorder<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            tick: 0,
+            last_tick: 0,
+            overflow: false,
+        }
+    }
+
+    /// start recording a new round, discarding the previous one.
+    ///
+    /// call right after the poll entering [`Game::ReadyAnimation`].
+    pub fn start(&mut self, settings: &Settings) {
+        self.buf[..MAGIC.len()].copy_from_slice(&MAGIC);
+        self.buf[MAGIC.len()] = VERSION;
+        self.buf[MAGIC.len() + 1] = settings.rule as u8;
+        self.buf[MAGIC.len() + 2] = settings.false_start as u8;
+        self.buf[MAGIC.len() + 3] = settings.speed as u8;
+        self.buf[MAGIC.len() + 4] = settings.countdown;
+        self.len = HEADER_LEN;
+        self.tick = 0;
+        self.last_tick = 0;
+        self.overflow = false;
+    }
+
+    /// advance one tick, call before each poll.
+    #[inline]
+    pub fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    /// record a button press.
+    pub fn press(&mut self, player: Players, other_held: bool) {
+        let mut kind = player as u8 * KIND_B;
+        if other_held {
+            kind |= KIND_OTHER_HELD;
+        }
+        self.push(kind, None);
+    }
+
+    /// record a gesture taken by the button latch.
+    pub fn gesture(&mut self, gesture: Gesture) {
+        self.push(KIND_GESTURE, Some(gesture.to_u8()));
+    }
+
+    /// record a random byte drawn by the game.
+    pub fn random(&mut self, value: u8) {
+        self.push(KIND_RANDOM, Some(value));
+    }
+
+    /// the log of the current round, empty before the first round.
+    #[inline]
+    pub fn log(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// records were dropped since the buffer is full.
+    #[inline]
+    pub fn overflowed(&self) -> bool {
+        self.overflow
+    }
+
+    fn push(&mut self, kind: u8, value: Option<u8>) {
+        // not started
+        if self.len == 0 || self.overflow {
+            return;
+        }
+        let mut record = [0u8; MAX_RECORD_LEN];
+        let delta = self.tick.wrapping_sub(self.last_tick);
+        let mut len = 1;
+        if delta < DELTA_EXTENDED {
+            record[0] = (delta as u8) << DELTA_SHIFT | kind;
+        } else {
+            record[0] = (DELTA_EXTENDED as u8) << DELTA_SHIFT | kind;
+            let mut rest = delta - DELTA_EXTENDED;
+            loop {
+                let byte = (rest & 0x7f) as u8;
+                rest >>= 7;
+                if rest == 0 {
+                    record[len] = byte;
+                    len += 1;
+                    break;
+                }
+                record[len] = byte | 0x80;
+                len += 1;
+            }
+        }
+        if let Some(value) = value {
+            record[len] = value;
+            len += 1;
+        }
+        if self.len + len > N {
+            self.overflow = true;
+            return;
+        }
+        self.buf[self.len..self.len + len].copy_from_slice(&record[..len]);
+        self.len += len;
+        self.last_tick = self.tick;
+    }
+}
+
+impl<const N: usize> Default for Recorder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decodes the records of a log.
+pub struct LogReader<'a> {
+    bytes: &'a [u8],
+    tick: u32,
+    settings: Settings,
+}
+
+impl<'a> LogReader<'a> {
+    pub fn new(log: &'a [u8]) -> Result<Self, LogError> {
+        match log {
+            [m0, m1, m2, VERSION, rule, false_start, speed, countdown @ 1..=MAX_COUNTDOWN, bytes @ ..]
+                if [*m0, *m1, *m2] == MAGIC =>
+            {
+                Ok(Self {
+                    bytes,
+                    tick: 0,
+                    settings: Settings {
+                        rule: Rule::from_u8(*rule).ok_or(LogError::Header)?,
+                        false_start: FalseStart::from_u8(*false_start).ok_or(LogError::Header)?,
+                        speed: Speed::from_u8(*speed).ok_or(LogError::Header)?,
+                        countdown: *countdown,
+                        ..Settings::new()
+                    },
+                })
+            }
+            _ => Err(LogError::Header),
+        }
+    }
+
+    /// the settings of the round.
+    #[inline]
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    fn next_byte(&mut self) -> Result<u8, LogError> {
+        let (byte, rest) = self.bytes.split_first().ok_or(LogError::Truncated)?;
+        self.bytes = rest;
+        Ok(*byte)
+    }
+
+    fn next_record(&mut self) -> Result<Record, LogError> {
+        let head = self.next_byte()?;
+        let mut delta = (head >> DELTA_SHIFT) as u32;
+        if delta == DELTA_EXTENDED {
+            let mut shift = 0;
+            loop {
+                let byte = self.next_byte()?;
+                delta = delta.wrapping_add(((byte & 0x7f) as u32) << shift);
+                shift += 7;
+                if byte & 0x80 == 0 || shift > 28 {
+                    break;
+                }
+            }
+        }
+        self.tick = self.tick.wrapping_add(delta);
+        let tick = self.tick;
+        match head & KIND_MASK {
+            KIND_RANDOM => Ok(Record::Random {
+                tick,
+                value: self.next_byte()?,
+            }),
+            KIND_GESTURE => Ok(Record::Gesture {
+                tick,
+                gesture: Gesture::from_u8(self.next_byte()?).ok_or(LogError::Kind(KIND_GESTURE))?,
+            }),
+            kind @ 0..=0b011 => Ok(Record::Press {
+                tick,
+                player: if kind & KIND_B == 0 {
+                    Players::A
+                } else {
+                    Players::B
+                },
+                other_held: kind & KIND_OTHER_HELD != 0,
+            }),
+            kind => Err(LogError::Kind(kind)),
+        }
+    }
+}
+
+impl<'a> Iterator for LogReader<'a> {
+    type Item = Result<Record, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let record = self.next_record();
+        if record.is_err() {
+            // stop at the first error
+            self.bytes = &[];
+        }
+        Some(record)
+    }
+}
+
+/// A step of the replayed round.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Step {
+    /// the countdown is over, the start signal plays.
+    Start { tick: u32 },
+    /// the dot moved to `px`, `position` along the spiral.
+    Dot {
+        tick: u32,
+        px: (u8, u8),
+        position: u8,
+    },
+    /// the round is over.
+    Winner { tick: u32, winner: Players },
+}
+
+/// Replays a log, yielding each step of the round.
+///
+/// The steps end without a winner when the round is left by a restart,
+/// or goes on [`END_TICKS`] after the last record, e.g. a tie of the tap
+/// rate, or a log cut short by an overflow.
+pub struct Replayer<'a> {
+    game: Game,
+    device: Device<ButtonLatch, Headless, Headless, LogInput<'a>>,
+    settings: Settings,
+    clock: TickClock,
+    tick: u32,
+    /// the tick all records were fed at.
+    end: Option<u32>,
+    done: bool,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new(log: &'a [u8]) -> Result<Self, LogError> {
+        let reader = LogReader::new(log)?;
+        let mut clock = TickClock::new();
+        Ok(Self {
+            game: Game::ready_animation(reader.settings().countdown, &mut clock, &mut Headless),
+            settings: *reader.settings(),
+            clock,
+            device: Device {
+                buttons: ButtonLatch::new(),
+                display: Headless,
+                sound: Headless,
+                rng: LogInput {
+                    reader,
+                    next: None,
+                    error: None,
+                },
+            },
+            tick: 0,
+            end: None,
+            done: false,
+        })
+    }
+}
+
+impl<'a> Iterator for Replayer<'a> {
+    type Item = Result<Step, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.tick = self.tick.wrapping_add(1);
+            let tick = self.tick;
+            let Device { buttons, rng, .. } = &mut self.device;
+            rng.feed(tick, buttons);
+            if rng.is_empty() {
+                let end = *self.end.get_or_insert(tick);
+                if tick.wrapping_sub(end) > END_TICKS {
+                    self.done = true;
+                    break;
+                }
+            }
+            let position = self.game.dot().map(DotState::position);
+            let ready = matches!(self.game, Game::ReadyAnimation { .. });
+            self.game
+                .poll(&mut self.clock, &mut self.device, &mut self.settings);
+            if let Some(error) = self.device.rng.error.take() {
+                self.done = true;
+                return Some(Err(error));
+            }
+            match &self.game {
+                Game::Playing { .. } if ready => return Some(Ok(Step::Start { tick })),
+                Game::Playing { dot, .. } if Some(dot.position()) != position => {
+                    return Some(Ok(Step::Dot {
+                        tick,
+                        px: *dot.px(),
+                        position: dot.position(),
+                    }));
+                }
+                Game::Result { winner, .. } => {
+                    self.done = true;
+                    return Some(Ok(Step::Winner {
+                        tick,
+                        winner: *winner,
+                    }));
+                }
+                // restarted
+                Game::IdleAnimation { .. } | Game::SettingsMenu { .. } => self.done = true,
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+/// Button presses and random bytes read from a log.
+struct LogInput<'a> {
+    reader: LogReader<'a>,
+    next: Option<Record>,
+    error: Option<LogError>,
+}
+
+impl<'a> LogInput<'a> {
+    fn peek(&mut self) -> Option<Record> {
+        if self.next.is_none() {
+            match self.reader.next() {
+                Some(Ok(record)) => self.next = Some(record),
+                Some(Err(error)) => self.error = Some(error),
+                None => (),
+            }
+        }
+        self.next
+    }
+
+    /// all the records are fed.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.next.is_none() && self.reader.bytes.is_empty()
+    }
+
+    /// press the buttons, and make the gestures, recorded before `tick`.
+    ///
+    /// like the latch on the board, a press does not press both buttons,
+    /// only a gesture of A and B does.
+    fn feed(&mut self, tick: u32, buttons: &mut ButtonLatch) {
+        while let Some(
+            record @ (Record::Press { tick: at, .. } | Record::Gesture { tick: at, .. }),
+        ) = self.peek()
+        {
+            if at >= tick {
+                break;
+            }
+            self.next = None;
+            match record {
+                Record::Press {
+                    player: Players::A, ..
+                } => buttons.press_a(false),
+                Record::Press { .. } => buttons.press_b(false),
+                Record::Gesture { gesture, .. } => {
+                    buttons.set_gesture(gesture);
+                }
+                Record::Random { .. } => (),
+            }
+        }
+    }
+}
+
+impl<'a> RandomSource for LogInput<'a> {
+    fn random_u8(&mut self) -> u8 {
+        match self.peek() {
+            Some(Record::Random { value, .. }) => {
+                self.next = None;
+                value
+            }
+            _ => {
+                self.error.get_or_insert(LogError::MissingRandom);
+                0
+            }
+        }
+    }
+}
+
+/// Devices that are not needed for replay.
+struct Headless;
+
+impl LedMatrix for Headless {
+    fn show(&mut self, _: &Frame) {}
+}
+
+impl SoundSink for Headless {
+    fn play_track(&mut self, _: Track) {}
+
+    fn stop(&mut self) {}
+}
+
+#[cfg(test)]
+mod test_replay {
+    use super::*;
+    use crate::{fake::FakeRng, Bot, ButtonInput, Difficulty};
+
+    /// Random source remembering the byte drawn.
+    struct Drawn {
+        value: u8,
+        drawn: Option<u8>,
+    }
+
+    impl RandomSource for Drawn {
+        fn random_u8(&mut self) -> u8 {
+            self.drawn = Some(self.value);
+            self.value
+        }
+    }
+
+    /// play a round the way the firmware records it, `press` making the
+    /// presses arriving before the next tick.
+    fn record(
+        random: u8,
+        mut settings: Settings,
+        mut press: impl FnMut(&Game, &mut ButtonLatch, &mut Recorder<16384>),
+    ) -> (Vec<u8>, Vec<Step>) {
+        let mut recorder = Recorder::<16384>::new();
+        let mut clock = TickClock::new();
+        let mut game = Game::ready_animation(settings.countdown, &mut clock, &mut Headless);
+        let mut device = Device {
+            buttons: ButtonLatch::new(),
+            display: Headless,
+            sound: Headless,
+            rng: Drawn {
+                value: random,
+                drawn: None,
+            },
+        };
+        let mut steps = Vec::new();
+        recorder.start(&settings);
+        for tick in 1.. {
+            recorder.tick();
+            let position = game.dot().map(DotState::position);
+            let ready = matches!(game, Game::ReadyAnimation { .. });
+            game.poll(&mut clock, &mut device, &mut settings);
+            if let Some(value) = device.rng.drawn.take() {
+                recorder.random(value);
+            }
+            match &game {
+                Game::Playing { .. } if ready => steps.push(Step::Start { tick }),
+                Game::Playing { dot, .. } if Some(dot.position()) != position => {
+                    steps.push(Step::Dot {
+                        tick,
+                        px: *dot.px(),
+                        position: dot.position(),
+                    })
+                }
+                Game::Result { winner, .. } => {
+                    steps.push(Step::Winner {
+                        tick,
+                        winner: *winner,
+                    });
+                    break;
+                }
+                _ => (),
+            }
+            press(&game, &mut device.buttons, &mut recorder);
+        }
+        assert!(!recorder.overflowed());
+        (recorder.log().to_vec(), steps)
+    }
+
+    /// a round of random presses.
+    fn record_round(random: u8, seed: u32, settings: Settings) -> (Vec<u8>, Vec<Step>) {
+        let mut lcg = seed;
+        record(random, settings, |_, buttons, recorder| {
+            lcg = lcg.wrapping_mul(1103515245).wrapping_add(12345);
+            match (lcg >> 16) % 16 {
+                0..=2 => {
+                    buttons.press_a(false);
+                    recorder.press(Players::A, false);
+                }
+                3..=4 => {
+                    buttons.press_b(true);
+                    recorder.press(Players::B, true);
+                }
+                _ => (),
+            }
+        })
+    }
+
+    #[test]
+    fn test_replay_reproduces_round() {
+        let settings = |rule, false_start| Settings {
+            rule,
+            false_start,
+            ..Settings::new()
+        };
+        for (random, seed, settings, dots) in [
+            (0, 1, settings(Rule::LastPress, FalseStart::Off), true),
+            (200, 2, settings(Rule::LastPress, FalseStart::Off), true),
+            (127, 3, settings(Rule::TapRate, FalseStart::Off), true),
+            (128, 4, settings(Rule::TapRate, FalseStart::Off), true),
+            (0, 5, settings(Rule::LastPress, FalseStart::Forfeit), false),
+            (200, 6, settings(Rule::TapRate, FalseStart::Penalty), false),
+            (
+                64,
+                7,
+                Settings {
+                    speed: Speed::Fast,
+                    countdown: 1,
+                    ..Settings::new()
+                },
+                true,
+            ),
+            (
+                192,
+                8,
+                Settings {
+                    rule: Rule::TapRate,
+                    speed: Speed::Slow,
+                    countdown: 5,
+                    ..Settings::new()
+                },
+                true,
+            ),
+        ] {
+            let (log, steps) = record_round(random, seed, settings);
+            let replayed = Replayer::new(&log)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let moved = steps.iter().any(|step| matches!(step, Step::Dot { .. }));
+            assert_eq!(moved, dots);
+            assert!(matches!(steps.last(), Some(Step::Winner { .. })));
+            assert_eq!(replayed, steps);
+        }
+    }
+
+    #[test]
+    fn test_replay_bot_round() {
+        let difficulty = Difficulty::Hard;
+        let settings = Settings {
+            bot: Some(difficulty),
+            ..Settings::new()
+        };
+        let mut bot = Bot::new();
+        let mut rng = FakeRng(128);
+        let mut ticks = 0;
+        let (log, steps) = record(100, settings, |game, buttons, recorder| {
+            // A held down between its presses, as the bot presses B
+            buttons.hold_a(true);
+            ticks += 1;
+            if ticks % 48 == 0 {
+                buttons.press_a(false);
+                recorder.press(Players::A, false);
+            }
+            if !matches!(game, Game::Playing { .. }) {
+                bot.reset(difficulty);
+            } else if bot.poll(difficulty, &mut rng) {
+                buttons.press_b(false);
+                recorder.press(Players::B, buttons.a_held());
+            }
+            assert!(!buttons.both_pressed());
+        });
+        let replayed = Replayer::new(&log)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(
+            steps.last(),
+            Some(Step::Winner {
+                winner: Players::B,
+                ..
+            })
+        ));
+        assert_eq!(replayed, steps);
+    }
+
+    #[test]
+    fn test_log_round_trip() {
+        let mut recorder = Recorder::<64>::new();
+        recorder.start(&Settings::new());
+        let mut expected = Vec::new();
+        for (delta, record) in [
+            (0, Record::Random { tick: 0, value: 7 }),
+            (
+                5,
+                Record::Press {
+                    tick: 0,
+                    player: Players::A,
+                    other_held: false,
+                },
+            ),
+            (
+                30,
+                Record::Press {
+                    tick: 0,
+                    player: Players::B,
+                    other_held: true,
+                },
+            ),
+            (
+                31,
+                Record::Press {
+                    tick: 0,
+                    player: Players::A,
+                    other_held: true,
+                },
+            ),
+            (
+                1000,
+                Record::Random {
+                    tick: 0,
+                    value: 255,
+                },
+            ),
+            (
+                100_000,
+                Record::Press {
+                    tick: 0,
+                    player: Players::B,
+                    other_held: false,
+                },
+            ),
+            (
+                2,
+                Record::Gesture {
+                    tick: 0,
+                    gesture: Gesture::LongPress(crate::Chord::AB),
+                },
+            ),
+        ] {
+            for _ in 0..delta {
+                recorder.tick();
+            }
+            let tick = expected.last().map_or(0, Record::tick) + delta;
+            expected.push(match record {
+                Record::Press {
+                    player, other_held, ..
+                } => {
+                    recorder.press(player, other_held);
+                    Record::Press {
+                        tick,
+                        player,
+                        other_held,
+                    }
+                }
+                Record::Random { value, .. } => {
+                    recorder.random(value);
+                    Record::Random { tick, value }
+                }
+                Record::Gesture { gesture, .. } => {
+                    recorder.gesture(gesture);
+                    Record::Gesture { tick, gesture }
+                }
+            });
+        }
+        let decoded = LogReader::new(recorder.log())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, expected);
+        // one byte per press with short deltas
+        assert_eq!(recorder.log().len(), HEADER_LEN + 2 + 1 + 1 + 2 + 4 + 4 + 2);
+    }
+
+    #[test]
+    fn test_unfinished_round() {
+        // the tap rate ties without presses
+        let mut recorder = Recorder::<64>::new();
+        let settings = Settings {
+            rule: Rule::TapRate,
+            ..Settings::new()
+        };
+        recorder.start(&settings);
+        recorder.tick();
+        recorder.random(0);
+        let steps = Replayer::new(recorder.log())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(steps[..], [Step::Start { .. }]));
+
+        // restarted while playing
+        for _ in 0..3 * TICK_HZ + 10 {
+            recorder.tick();
+        }
+        recorder.gesture(crate::RESTART);
+        for _ in 0..TICK_HZ {
+            recorder.tick();
+        }
+        recorder.press(Players::A, false);
+        let mut replayer = Replayer::new(recorder.log()).unwrap();
+        assert!(matches!(replayer.next(), Some(Ok(Step::Start { .. }))));
+        assert_eq!(replayer.next(), None);
+        assert_eq!(replayer.tick, 3 * TICK_HZ + 12);
+    }
+
+    #[test]
+    fn test_recorder_overflow() {
+        let mut recorder = Recorder::<10>::new();
+        recorder.press(Players::A, false);
+        assert!(recorder.log().is_empty());
+        recorder.start(&Settings::new());
+        recorder.press(Players::A, false);
+        recorder.press(Players::B, false);
+        recorder.press(Players::A, false);
+        assert!(recorder.overflowed());
+        assert_eq!(LogReader::new(recorder.log()).unwrap().count(), 2);
+        recorder.start(&Settings::new());
+        assert!(!recorder.overflowed());
+        assert_eq!(recorder.log().len(), HEADER_LEN);
+    }
+
+    #[test]
+    fn test_malformed_logs() {
+        assert!(matches!(
+            LogReader::new(b"TOW\x05\x00\x00\x01"),
+            Err(LogError::Header)
+        ));
+        assert!(matches!(
+            LogReader::new(b"TOX\x05\x00\x00\x01\x03"),
+            Err(LogError::Header)
+        ));
+        assert!(matches!(
+            LogReader::new(b"TOW\x04\x00\x00\x01\x03"),
+            Err(LogError::Header)
+        ));
+        for header in [
+            b"TOW\x05\x09\x00\x01\x03",
+            b"TOW\x05\x00\x03\x01\x03",
+            b"TOW\x05\x00\x00\x03\x03",
+            b"TOW\x05\x00\x00\x01\x00",
+            b"TOW\x05\x00\x00\x01\x06",
+        ] {
+            assert!(matches!(LogReader::new(header), Err(LogError::Header)));
+        }
+        let reader = LogReader::new(b"TOW\x05\x01\x02\x02\x05").unwrap();
+        assert_eq!(reader.settings().rule, Rule::TapRate);
+        assert_eq!(reader.settings().false_start, FalseStart::Penalty);
+        assert_eq!(reader.settings().speed, Speed::Fast);
+        assert_eq!(reader.settings().countdown, 5);
+        let truncated = LogReader::new(b"TOW\x05\x00\x00\x01\x03\x04")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(truncated, [Err(LogError::Truncated)]);
+        let bad_kind = LogReader::new(b"TOW\x05\x00\x00\x01\x03\x07")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(bad_kind, [Err(LogError::Kind(0b111))]);
+        let bad_gesture = LogReader::new(b"TOW\x05\x00\x00\x01\x03\x05\xff")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(bad_gesture, [Err(LogError::Kind(KIND_GESTURE))]);
+        let no_random = Replayer::new(b"TOW\x05\x00\x00\x01\x03")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(no_random, [Err(LogError::MissingRandom)]);
+    }
+}
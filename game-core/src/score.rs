@@ -0,0 +1,326 @@
+//! Best-of-N matches, keeping the score across rounds.
+use core::fmt::Write;
+
+use crate::{
+    ButtonInput, Device, Frame, Game, GameClock, LedMatrix, Players, RandomSource, Scroller,
+    Settings, SoundSink, Track,
+};
+
+/// cols showing the wins of player A and B.
+const SCORE_COLS: [u8; 2] = [0, 4];
+const ALL_ROWS: u8 = 0b11111;
+
+/// Number of rounds of a match.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BestOf {
+    /// free play, no match.
+    One = 1,
+    Three = 3,
+    Five = 5,
+    Seven = 7,
+}
+
+impl BestOf {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(BestOf::One),
+            3 => Some(BestOf::Three),
+            5 => Some(BestOf::Five),
+            7 => Some(BestOf::Seven),
+            _ => None,
+        }
+    }
+
+    /// round wins needed to win the match.
+    #[inline]
+    pub fn wins_needed(self) -> u8 {
+        self as u8 / 2 + 1
+    }
+}
+
+/// Round wins of each player.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Score {
+    pub a: u8,
+    pub b: u8,
+}
+
+impl Score {
+    pub const fn new() -> Self {
+        Self { a: 0, b: 0 }
+    }
+
+    #[inline]
+    pub fn add(&mut self, winner: Players) {
+        match winner {
+            Players::A => self.a += 1,
+            Players::B => self.b += 1,
+        }
+    }
+
+    #[inline]
+    pub fn wins(&self, player: Players) -> u8 {
+        match player {
+            Players::A => self.a,
+            Players::B => self.b,
+        }
+    }
+
+    /// the winner of the match, if any.
+    pub fn winner(&self, best_of: BestOf) -> Option<Players> {
+        let needed = best_of.wins_needed();
+        if self.a >= needed {
+            Some(Players::A)
+        } else if self.b >= needed {
+            Some(Players::B)
+        } else {
+            None
+        }
+    }
+}
+
+/// A match, playing rounds of [`Game`] until one player wins enough rounds.
+pub struct Match {
+    game: Game,
+    score: Score,
+    settings: Settings,
+    /// the winner of the match and the score, once it is over.
+    scroller: Scroller,
+}
+
+impl Match {
+    pub const fn new(settings: Settings) -> Self {
+        Self {
+            game: Game::new(),
+            score: Score::new(),
+            settings,
+            scroller: Scroller::new(),
+        }
+    }
+
+    /// the current round.
+    #[inline]
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    #[inline]
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    #[inline]
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    #[inline]
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+    }
+
+    /// the winner of the match, once it is over.
+    #[inline]
+    pub fn winner(&self) -> Option<Players> {
+        self.score.winner(self.settings.best_of)
+    }
+
+    /// advance the match by one tick.
+    ///
+    /// returns the winner when a round finishes.
+    pub fn poll<B, M, S, R>(
+        &mut self,
+        clock: &mut impl GameClock,
+        device: &mut Device<B, M, S, R>,
+    ) -> Option<Players>
+    where
+        B: ButtonInput,
+        M: LedMatrix,
+        S: SoundSink,
+        R: RandomSource,
+    {
+        let was_result = matches!(self.game, Game::Result { .. });
+        let was_idle = matches!(
+            self.game,
+            Game::IdleAnimation { .. } | Game::SettingsMenu { .. }
+        );
+
+        let round_winner = self.game.poll(clock, device, &mut self.settings);
+        if let Some(winner) = round_winner {
+            self.score.add(winner);
+            if self.settings.best_of != BestOf::One && self.winner().is_some() {
+                self.scroller.set_text("");
+                // writing to the scroller never fails
+                write!(
+                    self.scroller,
+                    "{} WINS {}-{}",
+                    winner.letter(),
+                    self.score.wins(winner),
+                    self.score.wins(winner.opponent())
+                )
+                .ok();
+                if self.settings.sound {
+                    // replace the round track started this tick.
+                    device.sound.play_track(Track::Fanfare);
+                }
+            }
+        }
+
+        match self.game {
+            // the referee restarted the match.
+            Game::IdleAnimation { .. } if !was_idle => self.score = Score::new(),
+            // next round of a finished match starts a new match.
+            Game::ReadyAnimation { .. } if was_result && self.winner().is_some() => {
+                self.score = Score::new();
+            }
+            // show the score after the round winner,
+            // or scroll the winner of the match.
+            Game::Result { .. }
+                if self.settings.best_of != BestOf::One && !self.game.winner_shown() =>
+            {
+                let mut frame = Frame::new();
+                if self.winner().is_some() {
+                    self.scroller.poll();
+                    self.scroller.draw(&mut frame);
+                } else {
+                    self.game.draw(clock, &self.settings, &mut frame);
+                    self.draw_score(&mut frame);
+                }
+                device.display.show(&frame);
+            }
+            _ => (),
+        }
+        round_winner
+    }
+
+    /// wins of both players as bars from the bottom of the side cols.
+    #[inline]
+    fn draw_score(&self, frame: &mut Frame) {
+        for player in [Players::A, Players::B] {
+            let wins = self.score.wins(player).min(5);
+            frame.set_col(
+                SCORE_COLS[player as usize],
+                ALL_ROWS << (5 - wins) & ALL_ROWS,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_score {
+    use super::*;
+    use crate::{fake::*, TickClock};
+
+    /// start the next round, press for one player until the round ends.
+    fn play_round(
+        m: &mut Match,
+        tick: &mut TickClock,
+        device: &mut FakeDevice,
+        a: bool,
+    ) -> Players {
+        while !matches!(m.game(), Game::ReadyAnimation { .. }) {
+            device.buttons.press_a(true);
+            m.poll(tick, device);
+        }
+        loop {
+            if a {
+                device.buttons.press_a(false);
+            } else {
+                device.buttons.press_b(false);
+            }
+            if let Some(winner) = m.poll(tick, device) {
+                return winner;
+            }
+        }
+    }
+
+    #[test]
+    fn test_wins_needed() {
+        assert_eq!(BestOf::One.wins_needed(), 1);
+        assert_eq!(BestOf::Three.wins_needed(), 2);
+        assert_eq!(BestOf::Five.wins_needed(), 3);
+        assert_eq!(BestOf::Seven.wins_needed(), 4);
+        let score = Score { a: 2, b: 3 };
+        assert_eq!(score.winner(BestOf::Five), Some(Players::B));
+        assert_eq!(score.winner(BestOf::Seven), None);
+    }
+
+    #[test]
+    fn test_best_of_three() {
+        let mut m = Match::new(Settings {
+            best_of: BestOf::Three,
+            ..Settings::new()
+        });
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+
+        assert_eq!(play_round(&mut m, &mut tick, &mut device, true), Players::A);
+        assert_eq!(m.score(), Score { a: 1, b: 0 });
+        assert_eq!(m.winner(), None);
+        assert_eq!(device.sound.0.last(), Some(&Track::Peppa));
+
+        // score of A shown after the winner
+        let mut lit = Vec::new();
+        for _ in 0..256 {
+            m.poll(&mut tick, &mut device);
+            if matches!(m.game(), Game::Result { .. }) && !m.game().winner_shown() {
+                lit.extend(device.display.lit());
+            }
+        }
+        assert!(lit.contains(&(4, 0)));
+        assert!(!lit.contains(&(3, 0)));
+        assert!(!lit.iter().any(|(_, y)| *y == 4));
+
+        assert_eq!(
+            play_round(&mut m, &mut tick, &mut device, false),
+            Players::B
+        );
+        assert_eq!(device.sound.0.last(), Some(&Track::Peppa));
+        assert_eq!(play_round(&mut m, &mut tick, &mut device, true), Players::A);
+        assert_eq!(m.score(), Score { a: 2, b: 1 });
+        assert_eq!(m.winner(), Some(Players::A));
+        assert_eq!(device.sound.0.last(), Some(&Track::Fanfare));
+
+        // the winner of the match scrolls by after the round winner
+        assert_eq!(m.scroller.text(), "A WINS 2-1");
+        let mut lit = Vec::new();
+        for _ in 0..2048 {
+            m.poll(&mut tick, &mut device);
+            lit.extend(device.display.lit());
+        }
+        assert!((0..5).all(|col| lit.iter().any(|(_, y)| *y == col)));
+        assert!(matches!(m.game(), Game::Result { .. }));
+
+        // a new match
+        play_round(&mut m, &mut tick, &mut device, false);
+        assert_eq!(m.score(), Score { a: 0, b: 1 });
+    }
+
+    #[test]
+    fn test_restart_clears_score() {
+        let mut m = Match::new(Settings {
+            best_of: BestOf::Three,
+            ..Settings::new()
+        });
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        play_round(&mut m, &mut tick, &mut device, true);
+        assert_eq!(m.score(), Score { a: 1, b: 0 });
+        device.buttons.set_gesture(crate::RESTART);
+        m.poll(&mut tick, &mut device);
+        assert!(matches!(m.game(), Game::IdleAnimation { .. }));
+        assert_eq!(m.score(), Score::new());
+    }
+
+    #[test]
+    fn test_free_play() {
+        let mut m = Match::new(Settings::new());
+        let mut tick = TickClock::new();
+        let mut device = fake_device(0);
+        for _ in 0..3 {
+            assert_eq!(play_round(&mut m, &mut tick, &mut device, true), Players::A);
+            assert_eq!(device.sound.0.last(), Some(&Track::Peppa));
+            assert_eq!(m.score(), Score { a: 1, b: 0 });
+        }
+    }
+}
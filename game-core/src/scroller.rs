@@ -0,0 +1,136 @@
+//! Text scrolling across the matrix, advanced by the game tick.
+use core::fmt;
+
+use crate::{draw_text, text_width, Frame, SIZE};
+
+/// chars of text a scroller holds, the rest is dropped.
+pub const TEXT_LEN: usize = 16;
+/// ticks per col scrolled (256HZ), 8 cols per second.
+const SCROLL_TICKS: u8 = 32;
+
+/// Scrolls a text from right to left, over and over.
+///
+/// the text is set with [`Scroller::set_text`], or appended with `write!`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Scroller {
+    text: [u8; TEXT_LEN],
+    len: u8,
+    /// cols scrolled since the text entered from the right.
+    offset: u8,
+    /// tick count (256HZ)
+    cnt: u8,
+}
+
+impl Scroller {
+    pub const fn new() -> Self {
+        Self {
+            text: [0; TEXT_LEN],
+            len: 0,
+            offset: 0,
+            cnt: 0,
+        }
+    }
+
+    /// replace the text, and scroll it from the start.
+    pub fn set_text(&mut self, text: &str) {
+        self.len = 0;
+        self.push_str(text);
+    }
+
+    #[inline]
+    pub fn text(&self) -> &str {
+        // only ascii is pushed
+        core::str::from_utf8(&self.text[..self.len as usize]).unwrap_or_default()
+    }
+
+    /// advance by one tick.
+    ///
+    /// returns true when the text has left the matrix, it enters again.
+    pub fn poll(&mut self) -> bool {
+        self.cnt += 1;
+        if self.cnt < SCROLL_TICKS {
+            return false;
+        }
+        self.cnt = 0;
+        self.offset += 1;
+        if self.offset < text_width(self.text()) + SIZE {
+            return false;
+        }
+        self.offset = 0;
+        true
+    }
+
+    /// draw the visible part of the text.
+    #[inline]
+    pub fn draw(&self, frame: &mut Frame) {
+        draw_text(self.text(), SIZE as i16 - self.offset as i16, frame);
+    }
+
+    fn push_str(&mut self, text: &str) {
+        for c in text.chars() {
+            if self.len as usize == TEXT_LEN {
+                break;
+            }
+            self.text[self.len as usize] = if c.is_ascii() { c as u8 } else { b'?' };
+            self.len += 1;
+        }
+        self.offset = 0;
+        self.cnt = 0;
+    }
+}
+
+impl fmt::Write for Scroller {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl Default for Scroller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_scroller {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn test_scroll_through() {
+        let mut scroller = Scroller::new();
+        write!(scroller, "A WINS {}-{}", 3, 1).unwrap();
+        assert_eq!(scroller.text(), "A WINS 3-1");
+        let width = text_width(scroller.text()) as usize;
+
+        // enters from the right, blank at first
+        let mut frame = Frame::new();
+        scroller.draw(&mut frame);
+        assert_eq!(frame, Frame::new());
+
+        let mut seen = Frame::new();
+        let mut ticks = 1;
+        while !scroller.poll() {
+            scroller.draw(&mut seen);
+            ticks += 1;
+        }
+        assert_eq!(ticks, (width + SIZE as usize) * SCROLL_TICKS as usize);
+        // every col of the matrix is passed by the text
+        assert!((0..SIZE).all(|col| (0..SIZE).any(|row| seen.is_lit(&(row, col)))));
+
+        // and starts over
+        let mut frame = Frame::new();
+        scroller.draw(&mut frame);
+        assert_eq!(frame, Frame::new());
+    }
+
+    #[test]
+    fn test_text_truncated() {
+        let mut scroller = Scroller::new();
+        scroller.set_text("A VERY LONG TEXT TO SCROLL");
+        assert_eq!(scroller.text(), "A VERY LONG TEXT");
+        scroller.set_text("é");
+        assert_eq!(scroller.text(), "?");
+    }
+}
@@ -0,0 +1,182 @@
+//! Options of the game.
+use crate::{BestOf, Difficulty, Duration};
+
+/// How the players move the dot.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rule {
+    /// the dot moves towards the player who pressed last.
+    LastPress = 0,
+    /// the dot moves towards the player who pressed more times
+    /// since the last step, and holds position on a tie.
+    TapRate = 1,
+}
+
+impl Rule {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Rule::LastPress),
+            1 => Some(Rule::TapRate),
+            _ => None,
+        }
+    }
+}
+
+/// What happens when a player presses during the countdown.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FalseStart {
+    /// presses during the countdown are ignored.
+    Off = 0,
+    /// the offender loses the round.
+    Forfeit = 1,
+    /// the countdown restarts, and the dot starts one step
+    /// towards the goal of the opponent.
+    Penalty = 2,
+}
+
+impl FalseStart {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(FalseStart::Off),
+            1 => Some(FalseStart::Forfeit),
+            2 => Some(FalseStart::Penalty),
+            _ => None,
+        }
+    }
+}
+
+/// How fast the dot runs.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Speed {
+    Slow = 0,
+    Normal = 1,
+    Fast = 2,
+}
+
+impl Speed {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Speed::Slow),
+            1 => Some(Speed::Normal),
+            2 => Some(Speed::Fast),
+            _ => None,
+        }
+    }
+
+    /// time between steps of the dot.
+    #[inline]
+    pub const fn step(self) -> Duration {
+        match self {
+            Speed::Slow => Duration::from_ms(94),
+            Speed::Normal => Duration::from_ms(62),
+            Speed::Fast => Duration::from_ms(47),
+        }
+    }
+
+    /// the next speed, wrapping around.
+    #[inline]
+    pub fn next(self) -> Self {
+        match self {
+            Speed::Slow => Speed::Normal,
+            Speed::Normal => Speed::Fast,
+            Speed::Fast => Speed::Slow,
+        }
+    }
+}
+
+/// Options of the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Settings {
+    pub rule: Rule,
+    pub best_of: BestOf,
+    pub false_start: FalseStart,
+    /// player B is played by the computer.
+    pub bot: Option<Difficulty>,
+    pub speed: Speed,
+    /// seconds of countdown, 1 to [`MAX_COUNTDOWN`].
+    pub countdown: u8,
+    /// play the tracks.
+    pub sound: bool,
+}
+
+/// the longest countdown.
+pub const MAX_COUNTDOWN: u8 = 5;
+/// bytes of [`Settings::to_bytes`].
+pub const SETTINGS_LEN: usize = 7;
+/// byte of `bot` for two players.
+const NO_BOT: u8 = 0xff;
+
+impl Settings {
+    pub const fn new() -> Self {
+        Self {
+            rule: Rule::LastPress,
+            best_of: BestOf::One,
+            false_start: FalseStart::Off,
+            bot: None,
+            speed: Speed::Normal,
+            countdown: 3,
+            sound: true,
+        }
+    }
+
+    /// next opponent of player A: human, then bots of rising difficulty.
+    pub fn cycle_bot(&mut self) {
+        self.bot = match self.bot {
+            None => Some(Difficulty::Easy),
+            Some(Difficulty::Easy) => Some(Difficulty::Medium),
+            Some(Difficulty::Medium) => Some(Difficulty::Hard),
+            Some(Difficulty::Hard) => None,
+        };
+    }
+
+    /// one byte per option.
+    pub fn to_bytes(&self) -> [u8; SETTINGS_LEN] {
+        [
+            self.rule as u8,
+            self.best_of as u8,
+            self.false_start as u8,
+            self.bot.map_or(NO_BOT, |bot| bot as u8),
+            self.speed as u8,
+            self.countdown,
+            self.sound as u8,
+        ]
+    }
+
+    /// parse [`Settings::to_bytes`], `None` if any option is invalid.
+    pub fn from_bytes(bytes: &[u8; SETTINGS_LEN]) -> Option<Self> {
+        let [rule, best_of, false_start, bot, speed, countdown, sound] = *bytes;
+        Some(Self {
+            rule: Rule::from_u8(rule)?,
+            best_of: BestOf::from_u8(best_of)?,
+            false_start: FalseStart::from_u8(false_start)?,
+            bot: match bot {
+                NO_BOT => None,
+                bot => Some(Difficulty::from_u8(bot)?),
+            },
+            speed: Speed::from_u8(speed)?,
+            countdown: match countdown {
+                1..=MAX_COUNTDOWN => countdown,
+                _ => return None,
+            },
+            sound: match sound {
+                0 => false,
+                1 => true,
+                _ => return None,
+            },
+        })
+    }
+
+    /// one more second of countdown, wrapping around.
+    #[inline]
+    pub fn cycle_countdown(&mut self) {
+        self.countdown = self.countdown % MAX_COUNTDOWN + 1;
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,7 @@
 //! logic to compute dot movement.
 pub const CENTER: (u8, u8) = (2, 2);
-const SPIRAL_LEN: usize = 49;
+/// positions along the spiral, from the goal of A to the goal of B.
+pub const SPIRAL_LEN: usize = 49;
 const START: i8 = 24;
 const COORD_MASK: i8 = 0b0011_1111u8 as i8;
 const CLOCKWISE_MASK: i8 = 0b1000_0000u8 as i8;
@@ -86,6 +87,12 @@ impl DotState {
         self.next();
     }
 
+    /// steps along the spiral from the goal of A.
+    #[inline]
+    pub fn position(&self) -> u8 {
+        self.pos() as u8
+    }
+
     #[inline]
     pub fn is_left(&self) -> bool {
         self.pos() == LEFT
@@ -112,3 +119,15 @@ impl DotState {
         self.0 & COORD_MASK
     }
 }
+
+/// the coordinate of a position along the spiral.
+#[inline]
+pub fn spiral_px(position: u8) -> Option<(u8, u8)> {
+    SPIRAL_TABLE.get(position as usize).copied()
+}
+
+impl Default for DotState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,413 @@
+//! Persistent store of the settings and the lifetime stats.
+//!
+//! The store is a ring of flash pages. Each page starts with a header of
+//! a sequence number followed by [`PAGE_MAGIC`], then fixed size records
+//! are appended to it, each ending with a CRC-32 of the record. When a
+//! page is full, the next page of the ring is erased and started with the
+//! next sequence number, so the pages wear evenly.
+//!
+//! A record is complete once its CRC is written, and a page is started
+//! once its magic is written. On [`Store::open`], the last complete record
+//! of the newest started page is recovered, or the last of the page before
+//! if power was lost right after starting a new page.
+use crate::{Players, Settings, SETTINGS_LEN};
+
+/// Flash memory, erased to `0xff` by pages and written by words.
+///
+/// offsets are relative to the start of the store,
+/// and writes can only clear bits.
+pub trait Flash {
+    /// bytes per page.
+    const PAGE_SIZE: u32;
+    /// pages of the store, at least 2.
+    const PAGES: u32;
+
+    fn read_word(&mut self, offset: u32) -> u32;
+
+    fn write_word(&mut self, offset: u32, word: u32);
+
+    fn erase_page(&mut self, page: u32);
+}
+
+/// `b"TOWS"`, marks a started page.
+pub const PAGE_MAGIC: u32 = u32::from_le_bytes(*b"TOWS");
+const HEADER_LEN: u32 = 8;
+const RECORD_MARKER: u8 = 0xa5;
+/// marker, settings, rounds, wins of A and B, CRC.
+const RECORD_LEN: usize = 24;
+const CRC_OFFSET: usize = RECORD_LEN - 4;
+const ERASED: u32 = u32::MAX;
+
+/// Lifetime stats.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Stats {
+    /// rounds played.
+    pub rounds: u32,
+    pub wins_a: u32,
+    pub wins_b: u32,
+}
+
+impl Stats {
+    pub const fn new() -> Self {
+        Self {
+            rounds: 0,
+            wins_a: 0,
+            wins_b: 0,
+        }
+    }
+
+    /// count a round won by `winner`.
+    pub fn add(&mut self, winner: Players) {
+        self.rounds = self.rounds.saturating_add(1);
+        match winner {
+            Players::A => self.wins_a = self.wins_a.saturating_add(1),
+            Players::B => self.wins_b = self.wins_b.saturating_add(1),
+        }
+    }
+}
+
+/// Contents of the store.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Saved {
+    pub settings: Settings,
+    pub stats: Stats,
+}
+
+impl Saved {
+    fn to_record(self) -> [u8; RECORD_LEN] {
+        let mut record = [0; RECORD_LEN];
+        record[0] = RECORD_MARKER;
+        record[1..8].copy_from_slice(&self.settings.to_bytes());
+        record[8..12].copy_from_slice(&self.stats.rounds.to_le_bytes());
+        record[12..16].copy_from_slice(&self.stats.wins_a.to_le_bytes());
+        record[16..20].copy_from_slice(&self.stats.wins_b.to_le_bytes());
+        let crc = crc32(&record[..CRC_OFFSET]);
+        record[CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+        record
+    }
+
+    fn from_record(record: &[u8; RECORD_LEN]) -> Option<Self> {
+        let word = |at: usize| {
+            u32::from_le_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]])
+        };
+        if record[0] != RECORD_MARKER || word(CRC_OFFSET) != crc32(&record[..CRC_OFFSET]) {
+            return None;
+        }
+        let mut settings = [0; SETTINGS_LEN];
+        settings.copy_from_slice(&record[1..8]);
+        Some(Self {
+            settings: Settings::from_bytes(&settings)?,
+            stats: Stats {
+                rounds: word(8),
+                wins_a: word(12),
+                wins_b: word(16),
+            },
+        })
+    }
+}
+
+/// Wear-levelled store of [`Saved`] records.
+pub struct Store<F> {
+    flash: F,
+    /// the page written and its sequence number, if any.
+    page: Option<(u32, u32)>,
+    /// the next free slot of the page.
+    slot: u32,
+}
+
+impl<F: Flash> Store<F> {
+    /// records per page.
+    const SLOTS: u32 = (F::PAGE_SIZE - HEADER_LEN) / RECORD_LEN as u32;
+
+    /// open the store, returns the last complete record if any.
+    pub fn open(flash: F) -> (Self, Option<Saved>) {
+        let mut store = Self {
+            flash,
+            page: None,
+            slot: 0,
+        };
+        for page in 0..F::PAGES {
+            if let Some(seq) = store.header(page) {
+                if store.page.is_none_or(|(_, newest)| seq > newest) {
+                    store.page = Some((page, seq));
+                }
+            }
+        }
+        let Some((page, seq)) = store.page else {
+            return (store, None);
+        };
+        store.slot = store.free_slot(page);
+        let saved = store.last_record(page).or_else(|| {
+            // power lost right after starting the page
+            let prev = (page + F::PAGES - 1) % F::PAGES;
+            match store.header(prev) {
+                Some(prev_seq) if prev_seq.wrapping_add(1) == seq => store.last_record(prev),
+                _ => None,
+            }
+        });
+        (store, saved)
+    }
+
+    /// append a record, starting the next page if the current one is full.
+    pub fn save(&mut self, saved: &Saved) {
+        let page = match self.page {
+            Some((page, _)) if self.slot < Self::SLOTS => page,
+            current => {
+                let (page, seq) = current.map_or((0, 0), |(page, seq)| {
+                    ((page + 1) % F::PAGES, seq.wrapping_add(1))
+                });
+                self.flash.erase_page(page);
+                let base = page * F::PAGE_SIZE;
+                self.flash.write_word(base, seq);
+                // the magic last, once the sequence number is written
+                self.flash.write_word(base + 4, PAGE_MAGIC);
+                self.page = Some((page, seq));
+                self.slot = 0;
+                page
+            }
+        };
+        let offset = Self::slot_offset(page, self.slot);
+        // the CRC last, completing the record
+        for (i, word) in saved.to_record().chunks_exact(4).enumerate() {
+            let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+            self.flash.write_word(offset + 4 * i as u32, word);
+        }
+        self.slot += 1;
+    }
+
+    /// the flash backend.
+    #[inline]
+    pub fn flash(&mut self) -> &mut F {
+        &mut self.flash
+    }
+
+    /// sequence number of a started page.
+    fn header(&mut self, page: u32) -> Option<u32> {
+        let base = page * F::PAGE_SIZE;
+        match self.flash.read_word(base + 4) {
+            PAGE_MAGIC => Some(self.flash.read_word(base)),
+            _ => None,
+        }
+    }
+
+    /// the slot after the last written one, complete or not.
+    fn free_slot(&mut self, page: u32) -> u32 {
+        (0..Self::SLOTS)
+            .rev()
+            .find(|slot| {
+                let offset = Self::slot_offset(page, *slot);
+                (0..RECORD_LEN as u32 / 4).any(|i| self.flash.read_word(offset + 4 * i) != ERASED)
+            })
+            .map_or(0, |slot| slot + 1)
+    }
+
+    /// the last complete record of a page.
+    fn last_record(&mut self, page: u32) -> Option<Saved> {
+        (0..Self::SLOTS).rev().find_map(|slot| {
+            let offset = Self::slot_offset(page, slot);
+            let mut record = [0; RECORD_LEN];
+            for (i, bytes) in record.chunks_exact_mut(4).enumerate() {
+                bytes.copy_from_slice(&self.flash.read_word(offset + 4 * i as u32).to_le_bytes());
+            }
+            Saved::from_record(&record)
+        })
+    }
+
+    #[inline]
+    fn slot_offset(page: u32, slot: u32) -> u32 {
+        page * F::PAGE_SIZE + HEADER_LEN + slot * RECORD_LEN as u32
+    }
+}
+
+/// CRC-32 (IEEE).
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test_store {
+    use super::*;
+    use crate::{BestOf, Difficulty, Rule, Speed};
+
+    /// pages of 64 bytes, two records each.
+    const PAGE_SIZE: usize = 64;
+
+    enum Power {
+        On,
+        /// lost during this operation.
+        Lost,
+        Off,
+    }
+
+    /// Flash in memory, losing power after a budget of operations.
+    struct MemFlash<const PAGES: usize> {
+        words: Vec<u32>,
+        erases: [usize; PAGES],
+        /// operations left before power is lost.
+        budget: Option<usize>,
+        lost: bool,
+    }
+
+    impl<const PAGES: usize> MemFlash<PAGES> {
+        fn new() -> Self {
+            Self {
+                words: vec![ERASED; PAGES * PAGE_SIZE / 4],
+                erases: [0; PAGES],
+                budget: None,
+                lost: false,
+            }
+        }
+
+        /// the same contents, powered again.
+        fn reboot(&self) -> Self {
+            Self {
+                words: self.words.clone(),
+                ..Self::new()
+            }
+        }
+
+        fn power(&mut self) -> Power {
+            match &mut self.budget {
+                Some(0) if self.lost => Power::Off,
+                Some(0) => {
+                    self.lost = true;
+                    Power::Lost
+                }
+                Some(budget) => {
+                    *budget -= 1;
+                    Power::On
+                }
+                None => Power::On,
+            }
+        }
+    }
+
+    impl<const PAGES: usize> Flash for MemFlash<PAGES> {
+        const PAGE_SIZE: u32 = PAGE_SIZE as u32;
+        const PAGES: u32 = PAGES as u32;
+
+        fn read_word(&mut self, offset: u32) -> u32 {
+            assert_eq!(offset % 4, 0);
+            self.words[offset as usize / 4]
+        }
+
+        fn write_word(&mut self, offset: u32, word: u32) {
+            assert_eq!(offset % 4, 0);
+            if let Power::On = self.power() {
+                self.words[offset as usize / 4] &= word;
+            }
+        }
+
+        fn erase_page(&mut self, page: u32) {
+            let words = PAGE_SIZE / 4;
+            let start = page as usize * words;
+            let end = match self.power() {
+                Power::On => {
+                    self.erases[page as usize] += 1;
+                    start + words
+                }
+                // an interrupted erase clears half of the page
+                Power::Lost => start + words / 2,
+                Power::Off => start,
+            };
+            self.words[start..end].fill(ERASED);
+        }
+    }
+
+    fn saved(rounds: u32) -> Saved {
+        Saved {
+            settings: Settings {
+                rule: Rule::TapRate,
+                best_of: BestOf::Five,
+                bot: Some(Difficulty::Medium),
+                speed: Speed::Fast,
+                countdown: 4,
+                sound: false,
+                ..Settings::new()
+            },
+            stats: Stats {
+                rounds,
+                wins_a: rounds / 2,
+                wins_b: rounds - rounds / 2,
+            },
+        }
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let record = saved(1000).to_record();
+        assert_eq!(Saved::from_record(&record), Some(saved(1000)));
+        for i in 0..RECORD_LEN {
+            let mut corrupted = record;
+            corrupted[i] ^= 0x10;
+            assert_eq!(Saved::from_record(&corrupted), None);
+        }
+        let mut stats = Stats::new();
+        stats.add(Players::A);
+        stats.add(Players::B);
+        assert_eq!(stats, saved(2).stats);
+    }
+
+    #[test]
+    fn test_wear_levelling() {
+        let (mut store, loaded) = Store::open(MemFlash::<4>::new());
+        assert_eq!(loaded, None);
+        for rounds in 1..=100 {
+            store.save(&saved(rounds));
+            assert_eq!(Store::open(store.flash().reboot()).1, Some(saved(rounds)));
+        }
+        // 50 pages started
+        assert_eq!(store.flash().erases, [13, 13, 12, 12]);
+
+        // carry on after reopening
+        let (mut store, _) = Store::open(store.flash().reboot());
+        store.save(&saved(101));
+        assert_eq!(store.flash().erases, [0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_power_loss() {
+        // operations of the first saves, 3 for starting a page, 6 per record
+        let ops = [9, 6, 9, 6, 9, 6, 9, 6];
+        for budget in 0..ops.iter().sum() {
+            let mut flash = MemFlash::<3>::new();
+            flash.budget = Some(budget);
+            let (mut store, _) = Store::open(flash);
+            for rounds in 1..=ops.len() as u32 {
+                store.save(&saved(rounds));
+            }
+            // saves finished before power is lost
+            let done = ops
+                .iter()
+                .scan(0, |sum, ops| {
+                    *sum += ops;
+                    Some(*sum)
+                })
+                .take_while(|sum| *sum <= budget)
+                .count() as u32;
+
+            let (mut store, loaded) = Store::open(store.flash().reboot());
+            assert_eq!(loaded, (done > 0).then(|| saved(done)), "budget {budget}");
+
+            // the store keeps working after recovery
+            store.save(&saved(100));
+            assert_eq!(
+                Store::open(store.flash().reboot()).1,
+                Some(saved(100)),
+                "budget {budget}"
+            );
+        }
+    }
+}
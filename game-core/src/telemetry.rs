@@ -0,0 +1,375 @@
+//! Telemetry of the game, streamed over the UART of the board.
+//!
+//! Each [`Event`] is sent as a frame: the version byte, the kind, the
+//! instant in ticks of the RTC as 3 little endian bytes, the fields of
+//! the event, and a CRC-8 of all these. Frames are COBS encoded and end
+//! with a zero byte, so a [`Decoder`] joining the stream mid-frame syncs
+//! at the next zero.
+//!
+//! Fields: the [`Settings::to_bytes`] and the wins of A and B of a round
+//! start, none of a go, the position along the spiral of a step, the
+//! player of a press, and the winner and the wins of A and B of a result.
+use crate::{Instant, Players, Score, Settings, SETTINGS_LEN, SPIRAL_LEN};
+
+pub const VERSION: u8 = 2;
+const KIND_ROUND_START: u8 = 0;
+const KIND_STEP: u8 = 1;
+const KIND_PRESS: u8 = 2;
+const KIND_RESULT: u8 = 3;
+const KIND_GO: u8 = 4;
+/// version, kind and instant.
+const HEADER_LEN: usize = 5;
+/// the longest payload, a round start, with its CRC.
+const MAX_PAYLOAD: usize = HEADER_LEN + SETTINGS_LEN + 2 + 1;
+/// bytes of the longest frame: a COBS code byte, the payload and the zero.
+pub const MAX_FRAME: usize = MAX_PAYLOAD + 2;
+/// COBS code of a block of 254 bytes, not followed by a zero.
+const COBS_FULL: u8 = 0xff;
+
+/// What happened in the game, at `at`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    /// the countdown of a round starts, `score` before the round.
+    RoundStart {
+        at: Instant,
+        settings: Settings,
+        score: Score,
+    },
+    /// the countdown is over, the start signal plays.
+    Go { at: Instant },
+    /// the dot moved to `position` along the spiral, see [`spiral_px`].
+    ///
+    /// [`spiral_px`]: crate::spiral_px
+    Step { at: Instant, position: u8 },
+    /// a button of `player` is pressed, or the computer presses.
+    Press { at: Instant, player: Players },
+    /// `winner` wins the round, `score` includes it.
+    Result {
+        at: Instant,
+        winner: Players,
+        score: Score,
+    },
+}
+
+/// Malformed frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FrameError {
+    /// not COBS, or too long, e.g. joining the stream mid-frame.
+    Encoding,
+    /// the CRC does not match, the frame is corrupted.
+    Crc,
+    /// frame of another version of the format.
+    Version(u8),
+    /// unknown event kind.
+    Kind(u8),
+    /// wrong length, or a field out of range.
+    Field,
+}
+
+impl Event {
+    #[inline]
+    pub fn at(&self) -> Instant {
+        match self {
+            Event::RoundStart { at, .. }
+            | Event::Go { at }
+            | Event::Step { at, .. }
+            | Event::Press { at, .. }
+            | Event::Result { at, .. } => *at,
+        }
+    }
+
+    /// write the frame, with its ending zero, returns its length.
+    pub fn encode(&self, frame: &mut [u8; MAX_FRAME]) -> usize {
+        let mut fields = [0; SETTINGS_LEN + 2];
+        let (kind, fields) = match self {
+            Event::RoundStart {
+                settings, score, ..
+            } => {
+                fields[..SETTINGS_LEN].copy_from_slice(&settings.to_bytes());
+                fields[SETTINGS_LEN..].copy_from_slice(&[score.a, score.b]);
+                (KIND_ROUND_START, &fields[..])
+            }
+            Event::Go { .. } => (KIND_GO, &fields[..0]),
+            Event::Step { position, .. } => {
+                fields[0] = *position;
+                (KIND_STEP, &fields[..1])
+            }
+            Event::Press { player, .. } => {
+                fields[0] = *player as u8;
+                (KIND_PRESS, &fields[..1])
+            }
+            Event::Result { winner, score, .. } => {
+                fields[..3].copy_from_slice(&[*winner as u8, score.a, score.b]);
+                (KIND_RESULT, &fields[..3])
+            }
+        };
+        let at = self.at().ticks().to_le_bytes();
+        let mut payload = [0; MAX_PAYLOAD];
+        let len = HEADER_LEN + fields.len();
+        payload[..HEADER_LEN].copy_from_slice(&[VERSION, kind, at[0], at[1], at[2]]);
+        payload[HEADER_LEN..len].copy_from_slice(fields);
+        payload[len] = crc8(&payload[..len]);
+        let len = cobs_encode(&payload[..=len], frame);
+        frame[len] = 0;
+        len + 1
+    }
+
+    /// parse a frame, without its ending zero.
+    pub fn decode(frame: &[u8]) -> Result<Self, FrameError> {
+        let mut payload = [0; MAX_PAYLOAD];
+        let len = cobs_decode(frame, &mut payload).ok_or(FrameError::Encoding)?;
+        let (payload, crc) = match payload[..len] {
+            [ref payload @ .., crc] if len > HEADER_LEN => (payload, crc),
+            _ => return Err(FrameError::Field),
+        };
+        if crc8(payload) != crc {
+            return Err(FrameError::Crc);
+        }
+        let (kind, at, fields) = match *payload {
+            [VERSION, kind, a, b, c, ref fields @ ..] => (
+                kind,
+                Instant::from_ticks(u32::from_le_bytes([a, b, c, 0])),
+                fields,
+            ),
+            [version, ..] => return Err(FrameError::Version(version)),
+            [] => return Err(FrameError::Field),
+        };
+        let player = |value| match value {
+            0 => Ok(Players::A),
+            1 => Ok(Players::B),
+            _ => Err(FrameError::Field),
+        };
+        match (kind, fields) {
+            (KIND_ROUND_START, [settings @ .., a, b]) => Ok(Event::RoundStart {
+                at,
+                settings: settings
+                    .try_into()
+                    .ok()
+                    .and_then(Settings::from_bytes)
+                    .ok_or(FrameError::Field)?,
+                score: Score { a: *a, b: *b },
+            }),
+            (KIND_GO, []) => Ok(Event::Go { at }),
+            (KIND_STEP, &[position]) if (position as usize) < SPIRAL_LEN => {
+                Ok(Event::Step { at, position })
+            }
+            (KIND_PRESS, &[value]) => Ok(Event::Press {
+                at,
+                player: player(value)?,
+            }),
+            (KIND_RESULT, &[winner, a, b]) => Ok(Event::Result {
+                at,
+                winner: player(winner)?,
+                score: Score { a, b },
+            }),
+            (KIND_ROUND_START..=KIND_GO, _) => Err(FrameError::Field),
+            (kind, _) => Err(FrameError::Kind(kind)),
+        }
+    }
+}
+
+/// Splits a stream of bytes into events.
+pub struct Decoder {
+    buf: [u8; MAX_FRAME],
+    len: usize,
+    /// the frame is longer than any event.
+    overflow: bool,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; MAX_FRAME],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// take the next byte of the stream, returns the event of the frame
+    /// it ends, if any.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Event, FrameError>> {
+        if byte != 0 {
+            match self.buf.get_mut(self.len) {
+                Some(slot) => {
+                    *slot = byte;
+                    self.len += 1;
+                }
+                None => self.overflow = true,
+            }
+            return None;
+        }
+        let frame = &self.buf[..self.len];
+        let overflow = self.overflow;
+        self.len = 0;
+        self.overflow = false;
+        match (frame, overflow) {
+            ([], false) => None,
+            (_, true) => Some(Err(FrameError::Encoding)),
+            (frame, false) => Some(Event::decode(frame)),
+        }
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// COBS encode `payload`, shorter than a full block, returns the length.
+fn cobs_encode(payload: &[u8], out: &mut [u8]) -> usize {
+    let mut code_at = 0;
+    let mut len = 1;
+    for &byte in payload {
+        if byte == 0 {
+            out[code_at] = (len - code_at) as u8;
+            code_at = len;
+        } else {
+            out[len] = byte;
+        }
+        len += 1;
+    }
+    out[code_at] = (len - code_at) as u8;
+    len
+}
+
+/// COBS decode `frame` into `out`, `None` if malformed or too long.
+fn cobs_decode(frame: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut len = 0;
+    let mut rest = frame;
+    while let [code, tail @ ..] = rest {
+        let block = tail.get(..(*code as usize).checked_sub(1)?)?;
+        out.get_mut(len..len + block.len())?.copy_from_slice(block);
+        len += block.len();
+        rest = &tail[block.len()..];
+        if *code != COBS_FULL && !rest.is_empty() {
+            *out.get_mut(len)? = 0;
+            len += 1;
+        }
+    }
+    Some(len)
+}
+
+/// CRC-8 (polynomial 0x07).
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = (crc << 1) ^ (0x07 & (crc >> 7).wrapping_neg());
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod test_telemetry {
+    use super::*;
+    use crate::{BestOf, Difficulty, Rule};
+
+    fn events() -> [Event; 5] {
+        let settings = Settings {
+            rule: Rule::TapRate,
+            best_of: BestOf::Five,
+            bot: Some(Difficulty::Hard),
+            ..Settings::new()
+        };
+        [
+            Event::RoundStart {
+                at: Instant::from_ticks(0xab_cdef),
+                settings,
+                score: Score { a: 1, b: 2 },
+            },
+            Event::Step {
+                at: Instant::from_ticks(0x100),
+                position: 20,
+            },
+            Event::Go {
+                at: Instant::from_ticks(0x12),
+            },
+            Event::Press {
+                at: Instant::from_ticks(0),
+                player: Players::B,
+            },
+            Event::Result {
+                at: Instant::from_ticks(0xff_ffff),
+                winner: Players::A,
+                score: Score { a: 3, b: 0 },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_frames() {
+        for event in events() {
+            let mut frame = [0; MAX_FRAME];
+            let len = event.encode(&mut frame);
+            assert_eq!(frame[len - 1], 0);
+            assert!(!frame[..len - 1].contains(&0));
+            assert_eq!(Event::decode(&frame[..len - 1]), Ok(event));
+        }
+        // step to 20 at 256: version, kind, 0x00 0x01 0x00, 20, CRC
+        let mut frame = [0; MAX_FRAME];
+        let len = events()[1].encode(&mut frame);
+        let payload = [VERSION, KIND_STEP, 0, 1, 0, 20];
+        let crc = crc8(&payload);
+        assert_eq!(frame[..len], [3, VERSION, KIND_STEP, 2, 1, 3, 20, crc, 0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut frame = [0; MAX_FRAME];
+        let len = events()[3].encode(&mut frame) - 1;
+        // flip a bit of the player
+        let mut bad = frame;
+        bad[len - 2] ^= 1;
+        assert_eq!(Event::decode(&bad[..len]), Err(FrameError::Crc));
+        // a code pointing past the end
+        assert_eq!(Event::decode(&[5, 1, 1]), Err(FrameError::Encoding));
+        let mut encode = |payload: &[u8]| {
+            let mut payload = payload.to_vec();
+            payload.push(crc8(&payload));
+            let len = cobs_encode(&payload, &mut frame);
+            Event::decode(&frame[..len])
+        };
+        assert_eq!(encode(&[1, 0, 0, 0, 0]), Err(FrameError::Version(1)));
+        assert_eq!(encode(&[VERSION, 9, 0, 0, 0]), Err(FrameError::Kind(9)));
+        for fields in [
+            &[VERSION, KIND_PRESS, 0, 0, 0, 2][..],
+            &[VERSION, KIND_STEP, 0, 0, 0, 49],
+            &[VERSION, KIND_STEP, 0, 0, 0, 1, 1],
+            &[VERSION, KIND_GO, 0, 0, 0, 1],
+            &[VERSION, KIND_STEP, 0],
+        ] {
+            assert_eq!(encode(fields), Err(FrameError::Field));
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut stream = vec![];
+        // joining mid-frame, and idle zeros
+        stream.extend_from_slice(&[7, 3, 9]);
+        stream.extend_from_slice(&[0, 0]);
+        for event in events() {
+            let mut frame = [0; MAX_FRAME];
+            let len = event.encode(&mut frame);
+            stream.extend_from_slice(&frame[..len]);
+        }
+        // noise longer than any frame
+        stream.extend_from_slice(&[1; 40]);
+        stream.push(0);
+        let mut frame = [0; MAX_FRAME];
+        let len = events()[0].encode(&mut frame);
+        stream.extend_from_slice(&frame[..len]);
+
+        let mut decoder = Decoder::new();
+        let decoded: Vec<_> = stream.iter().filter_map(|b| decoder.push(*b)).collect();
+        assert_eq!(decoded.len(), 8);
+        assert!(decoded[0].is_err());
+        let events = events().map(Ok);
+        assert_eq!(decoded[1..6], events);
+        assert_eq!(decoded[6], Err(FrameError::Encoding));
+        assert_eq!(decoded[7], events[0]);
+    }
+}
@@ -0,0 +1,400 @@
+//! Durations on the 256HZ RTC clock, and timers scheduled against its counter.
+use core::cmp::Reverse;
+
+use crate::{GameClock, GameTimer, TickAck, GAME_TIMERS};
+
+/// RTC ticks per second.
+pub const TICK_HZ: u32 = 256;
+/// the RTC counter has 24 bits.
+const COUNTER_MASK: u32 = 0xff_ffff;
+
+/// A duration in RTC ticks, rounded from milliseconds.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Duration(u32);
+
+impl Duration {
+    /// the nearest number of ticks.
+    pub const fn from_ms(ms: u32) -> Self {
+        Self((ms * TICK_HZ + 500) / 1000)
+    }
+
+    pub const fn from_secs(secs: u32) -> Self {
+        Self(secs * TICK_HZ)
+    }
+
+    pub const fn from_ticks(ticks: u32) -> Self {
+        Self(ticks)
+    }
+
+    #[inline]
+    pub const fn ticks(self) -> u32 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn as_ms(self) -> u32 {
+        self.0 * 1000 / TICK_HZ
+    }
+}
+
+/// A value of the RTC counter, wrapping around every 18 hours.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Instant(u32);
+
+impl Instant {
+    #[inline]
+    pub const fn from_ticks(counter: u32) -> Self {
+        Self(counter & COUNTER_MASK)
+    }
+
+    #[inline]
+    pub const fn ticks(self) -> u32 {
+        self.0
+    }
+
+    /// time since `earlier`, which must be less than 9 hours ago.
+    #[inline]
+    pub const fn since(self, earlier: Instant) -> Duration {
+        Duration(self.0.wrapping_sub(earlier.0) & COUNTER_MASK)
+    }
+
+    #[inline]
+    pub const fn after(self, duration: Duration) -> Instant {
+        Self::from_ticks(self.0.wrapping_add(duration.0))
+    }
+
+    #[inline]
+    pub const fn before(self, duration: Duration) -> Instant {
+        Self::from_ticks(self.0.wrapping_sub(duration.0))
+    }
+}
+
+/// Whether a timer starts again when it fires.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimerMode {
+    OneShot,
+    Periodic,
+}
+
+/// All timers are running.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimersFull;
+
+#[derive(Clone, Copy)]
+struct Timer<E> {
+    event: E,
+    start: Instant,
+    duration: Duration,
+    mode: TimerMode,
+}
+
+impl<E> Timer<E> {
+    #[inline]
+    fn remaining(&self, now: Instant) -> Duration {
+        Duration(self.duration.0.saturating_sub(now.since(self.start).0))
+    }
+}
+
+/// Up to `N` timers, each firing an event after its duration.
+///
+/// the owner of the clock passes the counter to [`Timers::poll`],
+/// when the compare channel set to [`Timers::next_deadline`] fires.
+pub struct Timers<E, const N: usize> {
+    timers: [Option<Timer<E>>; N],
+}
+
+impl<E: Copy + PartialEq, const N: usize> Timers<E, N> {
+    pub const fn new() -> Self {
+        Self { timers: [None; N] }
+    }
+
+    /// fire `event` after `duration`, and every `duration` if periodic.
+    ///
+    /// a timer running with the same event starts over.
+    pub fn start(
+        &mut self,
+        event: E,
+        now: Instant,
+        duration: Duration,
+        mode: TimerMode,
+    ) -> Result<(), TimersFull> {
+        let timer = Timer {
+            event,
+            start: now,
+            duration,
+            mode,
+        };
+        let slot = match self.position(event) {
+            Some(index) => &mut self.timers[index],
+            None => self
+                .timers
+                .iter_mut()
+                .find(|slot| slot.is_none())
+                .ok_or(TimersFull)?,
+        };
+        *slot = Some(timer);
+        Ok(())
+    }
+
+    /// returns true if the timer was running.
+    pub fn cancel(&mut self, event: E) -> bool {
+        self.position(event)
+            .map(|index| self.timers[index] = None)
+            .is_some()
+    }
+
+    #[inline]
+    pub fn is_running(&self, event: E) -> bool {
+        self.position(event).is_some()
+    }
+
+    /// time until the timer of `event` fires, if running.
+    pub fn remaining(&self, event: E, now: Instant) -> Option<Duration> {
+        self.position(event)
+            .and_then(|index| self.timers[index].as_ref())
+            .map(|timer| timer.remaining(now))
+    }
+
+    /// cancel all the timers.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.timers = [None; N];
+    }
+
+    /// when the next timer fires, if any.
+    pub fn next_deadline(&self, now: Instant) -> Option<Instant> {
+        self.timers
+            .iter()
+            .flatten()
+            .map(|timer| timer.remaining(now))
+            .min()
+            .map(|remaining| now.after(remaining))
+    }
+
+    /// take the event of a timer due at `now`, the most overdue first,
+    /// then the first started.
+    ///
+    /// call until `None` to take all of them.
+    pub fn poll(&mut self, now: Instant) -> Option<E> {
+        let slot = self
+            .timers
+            .iter_mut()
+            .filter(|slot| slot.is_some_and(|timer| timer.remaining(now) == Duration(0)))
+            .min_by_key(|slot| {
+                slot.map(|timer| Reverse(now.since(timer.start).0 - timer.duration.0))
+            })?;
+        let timer = slot.as_mut()?;
+        let event = timer.event;
+        match timer.mode {
+            TimerMode::OneShot => *slot = None,
+            // keep the period, even if polled late
+            TimerMode::Periodic => timer.start = timer.start.after(timer.duration),
+        }
+        Some(event)
+    }
+
+    #[inline]
+    fn position(&self, event: E) -> Option<usize> {
+        self.timers
+            .iter()
+            .position(|slot| slot.is_some_and(|timer| timer.event == event))
+    }
+}
+
+impl<E: Copy + PartialEq, const N: usize> Default for Timers<E, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The game ticks counted on the host, running the timers of the game
+/// as the RTC does on the board, for the simulator, replays and tests.
+#[derive(Default)]
+pub struct TickClock {
+    ticks: u32,
+    timers: Timers<GameTimer, GAME_TIMERS>,
+}
+
+impl TickClock {
+    pub const fn new() -> Self {
+        Self {
+            ticks: 0,
+            timers: Timers::new(),
+        }
+    }
+
+    /// ticks acknowledged so far.
+    #[inline]
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    #[inline]
+    pub fn now(&self) -> Instant {
+        Instant::from_ticks(self.ticks)
+    }
+}
+
+impl TickAck for TickClock {
+    #[inline]
+    fn ack_tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+    }
+}
+
+impl GameClock for TickClock {
+    fn start_timer(&mut self, timer: GameTimer, duration: Duration, mode: TimerMode) {
+        // never more timers than a state starts
+        self.timers.start(timer, self.now(), duration, mode).ok();
+    }
+
+    #[inline]
+    fn cancel_timers(&mut self) {
+        self.timers.clear();
+    }
+
+    #[inline]
+    fn poll_timer(&mut self) -> Option<GameTimer> {
+        self.timers.poll(self.now())
+    }
+
+    #[inline]
+    fn remaining(&self, timer: GameTimer) -> Option<Duration> {
+        self.timers.remaining(timer, self.now())
+    }
+}
+
+#[cfg(test)]
+mod test_timer {
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Event {
+        Save,
+        Blink,
+        Beep,
+    }
+
+    fn at(ticks: u32) -> Instant {
+        Instant::from_ticks(ticks)
+    }
+
+    /// events fired polling once per tick from `from` to `to`.
+    fn fired(timers: &mut Timers<Event, 2>, from: u32, to: u32) -> Vec<(u32, Event)> {
+        let mut fired = Vec::new();
+        for tick in from..=to {
+            while let Some(event) = timers.poll(at(tick)) {
+                fired.push((tick, event));
+            }
+        }
+        fired
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(Duration::from_ms(62).ticks(), 16);
+        assert_eq!(Duration::from_ms(500).ticks(), 128);
+        assert_eq!(Duration::from_secs(1).ticks(), TICK_HZ);
+        assert_eq!(Duration::from_ticks(16).as_ms(), 62);
+        assert_eq!(at(COUNTER_MASK).after(Duration::from_ticks(2)), at(1));
+        assert_eq!(at(1).since(at(COUNTER_MASK)).ticks(), 2);
+    }
+
+    #[test]
+    fn test_one_shot_and_periodic() {
+        let mut timers = Timers::new();
+        let second = Duration::from_secs(1);
+        timers
+            .start(Event::Save, at(10), second, TimerMode::OneShot)
+            .unwrap();
+        timers
+            .start(
+                Event::Blink,
+                at(0),
+                Duration::from_ms(125),
+                TimerMode::Periodic,
+            )
+            .unwrap();
+        assert_eq!(
+            timers.start(Event::Beep, at(0), second, TimerMode::OneShot),
+            Err(TimersFull)
+        );
+        assert_eq!(timers.next_deadline(at(10)), Some(at(32)));
+
+        let fired = fired(&mut timers, 10, 300);
+        let blinks: Vec<_> = fired
+            .iter()
+            .filter(|(_, event)| *event == Event::Blink)
+            .map(|(tick, _)| *tick)
+            .collect();
+        assert_eq!(blinks, [32, 64, 96, 128, 160, 192, 224, 256, 288]);
+        assert!(fired.contains(&(266, Event::Save)));
+        assert!(!timers.is_running(Event::Save));
+        assert_eq!(timers.next_deadline(at(300)), Some(at(320)));
+    }
+
+    #[test]
+    fn test_restart_and_cancel() {
+        let mut timers = Timers::new();
+        let duration = Duration::from_ticks(100);
+        timers
+            .start(Event::Save, at(0), duration, TimerMode::OneShot)
+            .unwrap();
+        timers
+            .start(Event::Beep, at(0), duration, TimerMode::OneShot)
+            .unwrap();
+        assert_eq!(fired(&mut timers, 0, 49), []);
+        // restarting replaces the running timer
+        timers
+            .start(Event::Save, at(50), duration, TimerMode::OneShot)
+            .unwrap();
+        assert_eq!(fired(&mut timers, 50, 120), [(100, Event::Beep)]);
+        assert!(timers.cancel(Event::Save));
+        assert!(!timers.cancel(Event::Save));
+        assert_eq!(fired(&mut timers, 120, 200), []);
+        assert_eq!(timers.next_deadline(at(200)), None);
+
+        timers
+            .start(Event::Blink, at(200), duration, TimerMode::Periodic)
+            .unwrap();
+        assert_eq!(timers.remaining(Event::Blink, at(260)), Some(Duration(40)));
+        assert_eq!(timers.remaining(Event::Save, at(260)), None);
+        timers.clear();
+        assert!(!timers.is_running(Event::Blink));
+    }
+
+    #[test]
+    fn test_late_poll_and_wrap() {
+        let mut timers = Timers::<Event, 2>::new();
+        let start = COUNTER_MASK - 10;
+        timers
+            .start(
+                Event::Beep,
+                at(start),
+                Duration::from_ticks(20),
+                TimerMode::OneShot,
+            )
+            .unwrap();
+        timers
+            .start(
+                Event::Blink,
+                at(start),
+                Duration::from_ticks(5),
+                TimerMode::Periodic,
+            )
+            .unwrap();
+        assert_eq!(timers.poll(at(start + 4)), None);
+        // polled late, the most overdue first, periodic keeps its period
+        let late = at(start).after(Duration::from_ticks(20));
+        assert_eq!(timers.poll(late), Some(Event::Blink));
+        assert_eq!(timers.poll(late), Some(Event::Blink));
+        assert_eq!(timers.poll(late), Some(Event::Blink));
+        assert_eq!(timers.poll(late), Some(Event::Beep));
+        assert_eq!(timers.poll(late), Some(Event::Blink));
+        assert_eq!(timers.poll(late), None);
+        assert_eq!(
+            timers.next_deadline(late),
+            Some(late.after(Duration::from_ticks(5)))
+        );
+    }
+}
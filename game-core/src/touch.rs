@@ -0,0 +1,175 @@
+//! Touch of a capacitive pad, detected from its charge time.
+//!
+//! A finger on the pad adds capacitance, so the pad takes longer to
+//! charge. The charge time is compared to a baseline calibrated when
+//! starting, which then follows the slow drift of the untouched pad.
+use crate::{Chord, Gesture, PAUSE};
+
+/// samples averaged for the baseline.
+pub const CALIBRATION_SAMPLES: u8 = 32;
+/// charge time above the baseline for a touch.
+pub const TOUCH_THRESHOLD: u16 = 12;
+/// samples in a row past the threshold to change state.
+const CONFIRM_SAMPLES: u8 = 3;
+/// the baseline moves by 1/64 of the difference per untouched sample.
+const DRIFT_SHIFT: u32 = 6;
+
+/// What a touch of the logo does.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum LogoAction {
+    /// as both buttons pressed together, to get ready, leave the
+    /// settings or resume.
+    #[default]
+    Confirm,
+    /// pause the round.
+    Pause,
+    /// the referee ends the round, back to idle.
+    Restart,
+}
+
+/// a touch of the logo restarts the game.
+pub const RESTART: Gesture = Gesture::Tap(Chord::Logo);
+
+impl LogoAction {
+    /// the gesture made by a touch.
+    pub const fn gesture(self) -> Gesture {
+        match self {
+            LogoAction::Confirm => Gesture::Tap(Chord::AB),
+            LogoAction::Pause => PAUSE,
+            LogoAction::Restart => RESTART,
+        }
+    }
+}
+
+/// Detects touches from the charge times of the pad.
+#[derive(Clone, Copy, Debug)]
+pub struct TouchDetector {
+    threshold: u16,
+    /// samples left to calibrate.
+    calibrating: u8,
+    /// the baseline, `DRIFT_SHIFT` bits of fraction once calibrated,
+    /// the sum of the samples while calibrating.
+    baseline: u32,
+    touched: bool,
+    /// samples in a row for the other state.
+    streak: u8,
+}
+
+impl TouchDetector {
+    pub const fn new(threshold: u16) -> Self {
+        Self {
+            threshold,
+            calibrating: CALIBRATION_SAMPLES,
+            baseline: 0,
+            touched: false,
+            streak: 0,
+        }
+    }
+
+    /// calibrate again, the pad must not be touched.
+    pub fn recalibrate(&mut self) {
+        *self = Self::new(self.threshold);
+    }
+
+    #[inline]
+    pub fn is_calibrated(&self) -> bool {
+        self.calibrating == 0
+    }
+
+    #[inline]
+    pub fn is_touched(&self) -> bool {
+        self.touched
+    }
+
+    /// charge time of the untouched pad, once calibrated.
+    #[inline]
+    pub fn baseline(&self) -> u16 {
+        (self.baseline >> DRIFT_SHIFT) as u16
+    }
+
+    /// add a charge time.
+    ///
+    /// returns whether the pad is touched, when it changes.
+    pub fn sample(&mut self, value: u16) -> Option<bool> {
+        if self.calibrating > 0 {
+            self.baseline += value as u32;
+            self.calibrating -= 1;
+            if self.calibrating == 0 {
+                self.baseline = (self.baseline << DRIFT_SHIFT) / CALIBRATION_SAMPLES as u32;
+            }
+            return None;
+        }
+        let above = value.saturating_sub(self.baseline());
+        // half the threshold to release, so noise does not toggle it
+        let other = if self.touched {
+            above < self.threshold / 2
+        } else {
+            above >= self.threshold
+        };
+        if !other {
+            self.streak = 0;
+            if !self.touched {
+                self.baseline = self.baseline - (self.baseline >> DRIFT_SHIFT) + value as u32;
+            }
+            return None;
+        }
+        self.streak += 1;
+        if self.streak < CONFIRM_SAMPLES {
+            return None;
+        }
+        self.streak = 0;
+        self.touched = !self.touched;
+        Some(self.touched)
+    }
+}
+
+impl Default for TouchDetector {
+    fn default() -> Self {
+        Self::new(TOUCH_THRESHOLD)
+    }
+}
+
+#[cfg(test)]
+mod test_touch {
+    use super::*;
+
+    /// charge times of the logo, touched from sample 49 to 72.
+    const RECORDED: [u16; 96] = [
+        21, 22, 20, 21, 23, 21, 20, 22, 21, 21, 22, 20, 21, 21, 23, 22, //
+        20, 21, 22, 21, 21, 20, 22, 21, 22, 21, 21, 20, 21, 22, 21, 21, //
+        22, 21, 20, 21, 36, 21, 22, 21, 20, 21, 22, 21, 21, 22, 21, 20, //
+        29, 38, 41, 42, 40, 43, 41, 42, 44, 40, 41, 42, 43, 41, 40, 26, //
+        42, 41, 43, 42, 40, 41, 42, 41, 30, 24, 22, 21, 22, 21, 20, 22, //
+        21, 21, 22, 20, 21, 21, 22, 21, 20, 22, 21, 21, 22, 21, 20, 21, //
+    ];
+
+    #[test]
+    fn test_recorded_touch() {
+        let mut detector = TouchDetector::default();
+        let changes: Vec<_> = RECORDED
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| detector.sample(*value).map(|touched| (i, touched)))
+            .collect();
+        // the spike at 36 is too short
+        assert_eq!(changes, [(51, true), (75, false)]);
+        assert!(detector.is_calibrated());
+        assert_eq!(detector.baseline(), 21);
+    }
+
+    #[test]
+    fn test_drift_and_recalibrate() {
+        let mut detector = TouchDetector::new(10);
+        (0..CALIBRATION_SAMPLES).for_each(|_| assert_eq!(detector.sample(100), None));
+        assert!(detector.is_calibrated());
+        // a slow rise follows the baseline, without a touch
+        for value in (100..=160).flat_map(|value| [value; 16]) {
+            assert_eq!(detector.sample(value), None);
+        }
+        assert!(detector.baseline() > 150);
+        detector.recalibrate();
+        assert!(!detector.is_calibrated());
+        (0..CALIBRATION_SAMPLES).for_each(|_| assert_eq!(detector.sample(50), None));
+        assert_eq!(detector.baseline(), 50);
+    }
+}
@@ -0,0 +1,93 @@
+//! Command line options.
+use game_core::{BestOf, Difficulty, FalseStart, Rule, Settings};
+
+pub const USAGE: &str = "usage: simulator [--best-of <1|3|5|7>] [--tap-rate] \
+    [--false-start <forfeit|penalty>] [--bot <easy|medium|hard>] | --replay <log>";
+
+pub enum Command {
+    /// play the game with the settings.
+    Play(Settings),
+    /// print the steps of a recorded round.
+    Replay(String),
+}
+
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut settings = Settings::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => return args.next().map(Command::Replay).ok_or_else(usage),
+            "--best-of" => settings.best_of = parse_best_of(&args.next().ok_or_else(usage)?)?,
+            "--tap-rate" => settings.rule = Rule::TapRate,
+            "--bot" => settings.bot = Some(parse_bot(&args.next().ok_or_else(usage)?)?),
+            "--false-start" => {
+                settings.false_start = parse_false_start(&args.next().ok_or_else(usage)?)?
+            }
+            _ => return Err(usage()),
+        }
+    }
+    Ok(Command::Play(settings))
+}
+
+fn usage() -> String {
+    USAGE.to_string()
+}
+
+fn parse_best_of(n: &str) -> Result<BestOf, String> {
+    match n {
+        "1" => Ok(BestOf::One),
+        "3" => Ok(BestOf::Three),
+        "5" => Ok(BestOf::Five),
+        "7" => Ok(BestOf::Seven),
+        _ => Err("best of must be 1, 3, 5 or 7".to_string()),
+    }
+}
+
+fn parse_bot(difficulty: &str) -> Result<Difficulty, String> {
+    match difficulty {
+        "easy" => Ok(Difficulty::Easy),
+        "medium" => Ok(Difficulty::Medium),
+        "hard" => Ok(Difficulty::Hard),
+        _ => Err("bot must be easy, medium or hard".to_string()),
+    }
+}
+
+fn parse_false_start(option: &str) -> Result<FalseStart, String> {
+    match option {
+        "forfeit" => Ok(FalseStart::Forfeit),
+        "penalty" => Ok(FalseStart::Penalty),
+        _ => Err("false start must be forfeit or penalty".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test_args {
+    use super::*;
+
+    fn parse_str(args: &str) -> Result<Command, String> {
+        parse(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse() {
+        let Ok(Command::Play(settings)) = parse_str("--tap-rate --best-of 5") else {
+            panic!("expect play")
+        };
+        assert_eq!(settings.rule, Rule::TapRate);
+        assert_eq!(settings.best_of, BestOf::Five);
+        assert_eq!(settings.false_start, FalseStart::Off);
+        let Ok(Command::Play(settings)) = parse_str("--false-start penalty") else {
+            panic!("expect play")
+        };
+        assert_eq!(settings.false_start, FalseStart::Penalty);
+        assert!(parse_str("--false-start early").is_err());
+        let Ok(Command::Play(settings)) = parse_str("--bot hard") else {
+            panic!("expect play")
+        };
+        assert_eq!(settings.bot, Some(Difficulty::Hard));
+        assert!(parse_str("--bot").is_err());
+        assert!(matches!(parse_str("--replay x.bin"), Ok(Command::Replay(log)) if log == "x.bin"));
+        assert!(parse_str("--best-of 4").is_err());
+        assert!(parse_str("--best-of").is_err());
+        assert!(parse_str("--unknown").is_err());
+    }
+}
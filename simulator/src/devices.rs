@@ -0,0 +1,151 @@
+//! Devices of the simulator.
+use game_core::{
+    Bot, ButtonInput, ButtonLatch, Chord, Difficulty, Gesture, Players, RandomSource, SoundSink,
+    Track, PAUSE, RESTART,
+};
+
+/// A key counts as held for this many ticks after each key press,
+/// since terminals do not report key releases.
+const HOLD_TICKS: u16 = 64;
+
+/// Buttons driven by key presses.
+#[derive(Default)]
+pub struct KeyButtons {
+    latch: ButtonLatch,
+    held_a: u16,
+    held_b: u16,
+    /// computer opponent pressing B.
+    bot: Bot,
+}
+
+impl KeyButtons {
+    /// a key mapped to a button is pressed.
+    pub fn press(&mut self, player: Players) {
+        match player {
+            Players::A => {
+                self.latch.press_a(self.held_b > 0);
+                self.held_a = HOLD_TICKS;
+            }
+            Players::B => {
+                self.latch.press_b(self.held_a > 0);
+                self.held_b = HOLD_TICKS;
+            }
+        }
+    }
+
+    /// press A and hold it long, which key repeats cannot tell.
+    pub fn long_press_a(&mut self) {
+        self.press(Players::A);
+        self.latch.set_gesture(Gesture::LongPress(Chord::A));
+    }
+
+    /// pause at once, as a touch of the logo does, since key repeats
+    /// cannot tell a hold of A and B.
+    pub fn pause(&mut self) {
+        self.latch.set_gesture(PAUSE);
+    }
+
+    /// touch the logo, which the referee does to restart.
+    pub fn touch_logo(&mut self) {
+        self.latch.set_gesture(RESTART);
+    }
+
+    /// let the computer opponent press B, call once per tick.
+    pub fn poll_bot(
+        &mut self,
+        bot: Option<Difficulty>,
+        playing: bool,
+        rng: &mut impl RandomSource,
+    ) {
+        let Some(difficulty) = bot else {
+            return;
+        };
+        if !playing {
+            self.bot.reset(difficulty);
+        } else if self.bot.poll(difficulty, rng) {
+            // unlike a key, never presses both buttons
+            self.latch.press_b(false);
+            self.held_b = HOLD_TICKS;
+        }
+    }
+
+    /// release the keys not pressed recently, call once per tick.
+    pub fn tick(&mut self) {
+        self.held_a = self.held_a.saturating_sub(1);
+        self.held_b = self.held_b.saturating_sub(1);
+    }
+}
+
+impl ButtonInput for KeyButtons {
+    fn both_pressed(&self) -> bool {
+        self.latch.both_pressed()
+    }
+
+    fn last_a(&self) -> bool {
+        self.latch.last_a()
+    }
+
+    fn reset(&mut self) {
+        self.latch.reset()
+    }
+
+    fn set_last_a(&mut self) {
+        self.latch.set_last_a()
+    }
+
+    fn take_presses(&mut self) -> (u8, u8) {
+        self.latch.take_presses()
+    }
+
+    fn a_held(&self) -> bool {
+        self.held_a > 0
+    }
+
+    fn take_gesture(&mut self) -> Option<Gesture> {
+        self.latch.take_gesture()
+    }
+}
+
+/// Remembers the last track requested.
+#[derive(Default)]
+pub struct SimSound {
+    /// the track and the tick it started.
+    pub playing: Option<(Track, u32)>,
+    now: u32,
+}
+
+impl SimSound {
+    /// update current tick.
+    pub fn set_now(&mut self, now: u32) {
+        self.now = now;
+    }
+}
+
+impl SoundSink for SimSound {
+    fn play_track(&mut self, track: Track) {
+        self.playing = Some((track, self.now));
+    }
+
+    fn stop(&mut self) {
+        self.playing = None;
+    }
+}
+
+/// xorshift random number generator.
+pub struct XorShift(u32);
+
+impl XorShift {
+    pub fn new(seed: u32) -> Self {
+        // state must not be zero
+        Self(seed | 1)
+    }
+}
+
+impl RandomSource for XorShift {
+    fn random_u8(&mut self) -> u8 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 24) as u8
+    }
+}
@@ -0,0 +1,231 @@
+//! Terminal simulator of the tug of war game.
+//!
+//! Drives the game state machine at 256HZ, renders the LED matrix
+//! in the terminal, and maps keys to buttons A and B.
+//!
+//! See [`args::USAGE`] for the options,
+//! `simulator --replay <log>` prints the steps of a recorded round instead.
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use devices::*;
+use game_core::{
+    replay::{Replayer, Step},
+    BestOf, Device, FalseStart, Game, Match, MenuOption, Players, Rule, Settings, TickClock,
+};
+use matrix::*;
+use std::{
+    env, fs,
+    io::{self, Write},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+mod args;
+mod devices;
+mod matrix;
+
+/// 256HZ
+const TICK: Duration = Duration::from_nanos(1_000_000_000 / 256);
+/// redraw the screen every 8 ticks (32 fps).
+const FRAME_TICKS: u32 = 8;
+const KEY_A: char = 'a';
+const KEY_B: char = 'l';
+/// long press of A, terminals do not report how long a key is held.
+const KEY_HOLD_A: char = 'A';
+/// holds A and B, to pause a round.
+const KEY_PAUSE: char = 'p';
+/// touches the logo, to restart.
+const KEY_LOGO: char = 't';
+
+type SimDevice = Device<KeyButtons, SimMatrix, SimSound, XorShift>;
+
+fn main() -> io::Result<()> {
+    let settings = match args::parse(env::args().skip(1)) {
+        Ok(args::Command::Play(settings)) => settings,
+        Ok(args::Command::Replay(log)) => return replay(&log),
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+    };
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = run(&mut stdout, settings);
+    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+/// the game loop, returns when quit.
+fn run(out: &mut impl Write, settings: Settings) -> io::Result<()> {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.subsec_nanos());
+    let mut game = Match::new(settings);
+    let mut tick = TickClock::new();
+    let mut device = Device {
+        buttons: KeyButtons::default(),
+        display: SimMatrix::default(),
+        sound: SimSound::default(),
+        rng: XorShift::new(seed),
+    };
+    let start = Instant::now();
+    loop {
+        // handle keys until next tick
+        let next_tick = start + TICK * tick.ticks();
+        while let Some(timeout) = next_tick.checked_duration_since(Instant::now()) {
+            if !event::poll(timeout)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                if !handle_key(key, &mut device.buttons) {
+                    return Ok(());
+                }
+            }
+        }
+
+        device.sound.set_now(tick.ticks());
+        game.poll(&mut tick, &mut device);
+        let playing = matches!(game.game(), Game::Playing { .. });
+        device
+            .buttons
+            .poll_bot(game.settings().bot, playing, &mut device.rng);
+        device.buttons.tick();
+        device.display.sample();
+
+        if tick.ticks().is_multiple_of(FRAME_TICKS) {
+            draw(out, &game, &mut device)?;
+        }
+    }
+}
+
+/// returns false if quit.
+fn handle_key(key: KeyEvent, buttons: &mut KeyButtons) -> bool {
+    if key.kind == KeyEventKind::Release {
+        return true;
+    }
+    match key.code {
+        KeyCode::Char(KEY_A) => buttons.press(Players::A),
+        KeyCode::Char(KEY_B) => buttons.press(Players::B),
+        KeyCode::Char(KEY_HOLD_A) => buttons.long_press_a(),
+        KeyCode::Char(KEY_PAUSE) => buttons.pause(),
+        KeyCode::Char(KEY_LOGO) => buttons.touch_logo(),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+        KeyCode::Char('q') | KeyCode::Esc => return false,
+        _ => (),
+    }
+    true
+}
+
+fn draw(out: &mut impl Write, game: &Match, device: &mut SimDevice) -> io::Result<()> {
+    let mut lines = vec![
+        format!("tug of war  [{KEY_A}] button A  [{KEY_B}] button B  [q] quit"),
+        format!("press {KEY_A} and {KEY_B} together to get ready"),
+        format!("tap {KEY_B} three times when idle to change the opponent of A"),
+        format!("[{KEY_HOLD_A}] holds A to open the settings: {KEY_A} next option, {KEY_B} change"),
+        format!("[{KEY_PAUSE}] pauses a round, press {KEY_A} and {KEY_B} together to resume"),
+        format!("[{KEY_LOGO}] touches the logo, the referee restarts the game"),
+        String::new(),
+    ];
+    lines.extend(
+        device
+            .display
+            .take_pixels()
+            .iter()
+            .map(|row| format!("    {}", render_row(row))),
+    );
+    lines.push(String::new());
+    lines.push(format!("state: {}", state_name(game.game())));
+    let settings = game.settings();
+    if settings.rule == Rule::TapRate {
+        lines.push("rule: tap rate".to_string());
+    }
+    lines.push(
+        format!(
+            "speed: {:?}  countdown: {}  sound: {}",
+            settings.speed,
+            settings.countdown,
+            if settings.sound { "on" } else { "off" }
+        )
+        .to_lowercase(),
+    );
+    if let Some(difficulty) = settings.bot {
+        lines.push(format!("opponent: {difficulty:?} bot").to_lowercase());
+    }
+    match settings.false_start {
+        FalseStart::Off => (),
+        FalseStart::Forfeit => lines.push("false start: forfeit".to_string()),
+        FalseStart::Penalty => lines.push("false start: penalty".to_string()),
+    }
+    if settings.best_of != BestOf::One {
+        let score = game.score();
+        lines.push(format!(
+            "score: A {} - {} B (best of {})",
+            score.a, score.b, settings.best_of as u8
+        ));
+    }
+    lines.push(match device.sound.playing {
+        Some((track, at)) => format!("track: {track:?} (tick {at})"),
+        None => "track: -".to_string(),
+    });
+    for (i, line) in lines.iter().enumerate() {
+        queue!(
+            out,
+            cursor::MoveTo(0, i as u16),
+            terminal::Clear(ClearType::CurrentLine),
+            Print(line)
+        )?;
+    }
+    out.flush()
+}
+
+fn state_name(game: &Game) -> &'static str {
+    match game {
+        Game::IdleAnimation { .. } => "idle",
+        Game::ReadyAnimation { .. } => "ready",
+        Game::Playing { .. } => "playing",
+        Game::Paused { .. } => "paused",
+        Game::Result { winner, .. } => match winner {
+            Players::A => "A wins",
+            Players::B => "B wins",
+        },
+        Game::SettingsMenu { option, .. } => match option {
+            MenuOption::Mode => "settings: mode",
+            MenuOption::Rule => "settings: rule",
+            MenuOption::Speed => "settings: speed",
+            MenuOption::CountDown => "settings: countdown",
+            MenuOption::Sound => "settings: sound",
+            MenuOption::BestOf => "settings: best of",
+            MenuOption::FalseStart => "settings: false start",
+        },
+        Game::FalseStart { offender, .. } => match offender {
+            Players::A => "false start by A",
+            Players::B => "false start by B",
+        },
+    }
+}
+
+/// print the dot positions and the winner of a recorded round.
+fn replay(path: &str) -> io::Result<()> {
+    let log = fs::read(path)?;
+    let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}"));
+    let mut finished = false;
+    for step in Replayer::new(&log).map_err(invalid)? {
+        match step.map_err(invalid)? {
+            Step::Start { tick } => println!("{tick:>6}  start"),
+            Step::Dot {
+                tick, px: (x, y), ..
+            } => println!("{tick:>6}  dot ({x}, {y})"),
+            Step::Winner { tick, winner } => {
+                println!("{tick:>6}  {winner:?} wins");
+                finished = true;
+            }
+        }
+    }
+    if !finished {
+        println!("unfinished");
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! LED matrix emulated from the frames shown by the game.
+use game_core::{Frame, LedMatrix, MAX_BRIGHTNESS};
+
+const SIZE: usize = 5;
+
+/// characters for each brightness, from off to fully lit.
+const SHADES: &[u8; MAX_BRIGHTNESS as usize + 1] = b".,-:;+=*%#";
+
+/// Pixels of the matrix, the brightest each pixel has been.
+pub type Pixels = [[u8; SIZE]; SIZE];
+
+/// The frame shown by the game.
+///
+/// The frame may change every tick, faster than the terminal is
+/// rendered, so pixels are accumulated between renders, like the eye does.
+#[derive(Default)]
+pub struct SimMatrix {
+    frame: Frame,
+    pixels: Pixels,
+}
+
+impl SimMatrix {
+    /// record the pixels currently lit, call once per tick.
+    pub fn sample(&mut self) {
+        for (x, row) in self.pixels.iter_mut().enumerate() {
+            for (y, px) in row.iter_mut().enumerate() {
+                *px = (*px).max(self.frame.brightness(&(x as u8, y as u8)));
+            }
+        }
+    }
+
+    /// the pixels lit since last render.
+    pub fn take_pixels(&mut self) -> Pixels {
+        core::mem::take(&mut self.pixels)
+    }
+}
+
+impl LedMatrix for SimMatrix {
+    fn show(&mut self, frame: &Frame) {
+        self.frame = *frame;
+    }
+}
+
+/// render a row of the frame as characters, shaded by brightness.
+pub fn render_row(row: &[u8; SIZE]) -> String {
+    row.iter()
+        .map(|brightness| format!("{} ", SHADES[*brightness as usize] as char))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod test_matrix {
+    use super::*;
+
+    #[test]
+    fn test_pixels_accumulate_frames() {
+        let mut matrix = SimMatrix::default();
+        let mut frame = Frame::new();
+        frame.set(&(0, 0));
+        matrix.show(&frame);
+        matrix.sample();
+        let mut frame = Frame::new();
+        frame.set_col(1, 0b10000);
+        frame.set_brightness(&(0, 0), 2);
+        matrix.show(&frame);
+        matrix.sample();
+        let pixels = matrix.take_pixels();
+        assert_eq!(pixels[0][0], MAX_BRIGHTNESS);
+        assert_eq!(pixels[4][1], MAX_BRIGHTNESS);
+        assert_eq!(pixels.iter().flatten().filter(|lit| **lit > 0).count(), 2);
+        assert_eq!(matrix.take_pixels(), Pixels::default());
+    }
+
+    #[test]
+    fn test_render_row() {
+        assert_eq!(
+            render_row(&[MAX_BRIGHTNESS, 0, 0, 0, MAX_BRIGHTNESS]),
+            "# . . . #"
+        );
+        assert_eq!(render_row(&[1, 2, 3, 4, 5]), ", - : ; +");
+    }
+}
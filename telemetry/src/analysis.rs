@@ -0,0 +1,433 @@
+//! Metrics of the rounds, from the telemetry or from a match log.
+use game_core::{
+    replay::{LogError, LogReader, Record, Replayer, Step, END_TICKS},
+    telemetry::Event,
+    DotState, Instant, Players, Score, Settings,
+};
+use std::cmp::Ordering;
+
+/// A round, as timed by the board.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Round {
+    pub settings: Settings,
+    /// the countdown starts.
+    pub start: Instant,
+    /// the last start signal, none after a false start forfeit.
+    pub go: Option<Instant>,
+    /// presses after the start signal.
+    pub presses: Vec<(Instant, Players)>,
+    /// positions of the dot along the spiral.
+    pub steps: Vec<(Instant, u8)>,
+    /// the winner, none if the stream ends mid-round.
+    pub result: Option<(Instant, Players)>,
+}
+
+/// Metrics of a player, for a round or summed over rounds.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PlayerStats {
+    pub wins: u32,
+    /// rounds won by a false start of the opponent, without a start signal.
+    pub forfeits: u32,
+    /// presses after the start signal.
+    pub taps: u32,
+    /// from the start signals to the results.
+    pub play_ms: u32,
+    /// from the start signals to the first presses, of `reactions` rounds.
+    pub reaction_ms: u32,
+    pub reactions: u32,
+    /// from the start signals to the results of the rounds won.
+    pub win_ms: u32,
+    /// steps of the dot towards the goal of the player.
+    pub held: u32,
+    /// all moves of the dot.
+    pub steps: u32,
+}
+
+impl PlayerStats {
+    pub fn taps_per_sec(&self) -> Option<f64> {
+        (self.play_ms > 0).then(|| self.taps as f64 * 1000.0 / self.play_ms as f64)
+    }
+
+    pub fn mean_reaction_ms(&self) -> Option<u32> {
+        (self.reactions > 0).then(|| self.reaction_ms / self.reactions)
+    }
+
+    /// mean time to win.
+    pub fn mean_win_ms(&self) -> Option<u32> {
+        let timed = self.wins - self.forfeits;
+        (timed > 0).then(|| self.win_ms / timed)
+    }
+
+    /// share of the steps towards the goal of the player.
+    pub fn held_share(&self) -> Option<f64> {
+        (self.steps > 0).then(|| self.held as f64 / self.steps as f64)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.wins += other.wins;
+        self.forfeits += other.forfeits;
+        self.taps += other.taps;
+        self.play_ms += other.play_ms;
+        self.reaction_ms += other.reaction_ms;
+        self.reactions += other.reactions;
+        self.win_ms += other.win_ms;
+        self.held += other.held;
+        self.steps += other.steps;
+    }
+}
+
+/// Metrics of a round.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RoundStats {
+    pub settings: Settings,
+    pub winner: Option<Players>,
+    /// from the start signal to the result.
+    pub time_to_win_ms: Option<u32>,
+    /// of A and B.
+    pub players: [PlayerStats; 2],
+}
+
+impl Round {
+    fn new(start: Instant, settings: Settings) -> Self {
+        Self {
+            settings,
+            start,
+            go: None,
+            presses: vec![],
+            steps: vec![],
+            result: None,
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        match event {
+            // presses before are false starts, and the countdown
+            // restarts after a penalty
+            Event::Go { at } => {
+                self.go = Some(at);
+                self.presses.clear();
+            }
+            Event::Press { at, player } if self.go.is_some() => self.presses.push((at, player)),
+            Event::Step { at, position } => self.steps.push((at, position)),
+            Event::Result { at, winner, .. } => self.result = Some((at, winner)),
+            Event::RoundStart { .. } | Event::Press { .. } => (),
+        }
+    }
+
+    /// the dot moving towards the goal of A at the left end of the
+    /// spiral is held by A, and the other way by B.
+    pub fn stats(&self) -> RoundStats {
+        let mut players = [PlayerStats::default(); 2];
+        let time_to_win_ms = match (self.go, self.result) {
+            (Some(go), Some((at, _))) => Some(at.since(go).as_ms()),
+            _ => None,
+        };
+        if let Some((_, winner)) = self.result {
+            let stats = &mut players[winner as usize];
+            stats.wins = 1;
+            match time_to_win_ms {
+                Some(ms) => stats.win_ms = ms,
+                None => stats.forfeits = 1,
+            }
+        }
+        for (at, player) in &self.presses {
+            let stats = &mut players[*player as usize];
+            if stats.taps == 0 {
+                if let Some(go) = self.go {
+                    stats.reaction_ms = at.since(go).as_ms();
+                    stats.reactions = 1;
+                }
+            }
+            stats.taps += 1;
+        }
+        let mut position = DotState::new().position();
+        let mut steps = 0;
+        for (_, next) in &self.steps {
+            match next.cmp(&position) {
+                Ordering::Less => players[Players::A as usize].held += 1,
+                Ordering::Greater => players[Players::B as usize].held += 1,
+                Ordering::Equal => continue,
+            }
+            steps += 1;
+            position = *next;
+        }
+        for stats in &mut players {
+            stats.play_ms = time_to_win_ms.unwrap_or(0);
+            stats.steps = steps;
+        }
+        RoundStats {
+            settings: self.settings,
+            winner: self.result.map(|(_, winner)| winner),
+            time_to_win_ms,
+            players,
+        }
+    }
+}
+
+/// split the telemetry into rounds, skipping the events before the
+/// first round start, e.g. when joining mid-round.
+pub fn rounds(events: impl IntoIterator<Item = Event>) -> Vec<Round> {
+    let mut rounds: Vec<Round> = vec![];
+    for event in events {
+        match event {
+            Event::RoundStart { at, settings, .. } => rounds.push(Round::new(at, settings)),
+            event => {
+                if let Some(round) = rounds.last_mut().filter(|round| round.result.is_none()) {
+                    round.push(event);
+                }
+            }
+        }
+    }
+    rounds
+}
+
+/// the events of a round recorded by the board, timed in ticks from
+/// the start of its countdown, up to [`END_TICKS`] after its last record.
+pub fn log_events(log: &[u8]) -> Result<Vec<Event>, LogError> {
+    let reader = LogReader::new(log)?;
+    let settings = *reader.settings();
+    let mut last = 0;
+    for record in LogReader::new(log)? {
+        last = record?.tick();
+    }
+    let at = Instant::from_ticks;
+    let mut events = vec![Event::RoundStart {
+        at: at(0),
+        settings,
+        score: Score::new(),
+    }];
+    // presses are recorded after the poll of their tick
+    let mut presses = reader.filter_map(|record| match record {
+        Ok(Record::Press { tick, player, .. }) => Some(Ok((tick, player))),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    });
+    let mut next_press = presses.next().transpose()?;
+    for step in Replayer::new(log)? {
+        let step = step?;
+        let tick = match step {
+            Step::Start { tick } | Step::Dot { tick, .. } | Step::Winner { tick, .. } => tick,
+        };
+        if tick > last + END_TICKS {
+            break;
+        }
+        while let Some((press, player)) = next_press.filter(|(press, _)| *press < tick) {
+            events.push(Event::Press {
+                at: at(press),
+                player,
+            });
+            next_press = presses.next().transpose()?;
+        }
+        events.push(match step {
+            Step::Start { tick } => Event::Go { at: at(tick) },
+            Step::Dot { tick, position, .. } => Event::Step {
+                at: at(tick),
+                position,
+            },
+            Step::Winner { tick, winner } => {
+                let mut score = Score::new();
+                score.add(winner);
+                Event::Result {
+                    at: at(tick),
+                    winner,
+                    score,
+                }
+            }
+        });
+    }
+    while let Some((press, player)) = next_press {
+        events.push(Event::Press {
+            at: at(press),
+            player,
+        });
+        next_press = presses.next().transpose()?;
+    }
+    Ok(events)
+}
+
+/// sum the metrics of the players over the finished rounds.
+pub fn totals(rounds: &[RoundStats]) -> [PlayerStats; 2] {
+    let mut totals = [PlayerStats::default(); 2];
+    for round in rounds.iter().filter(|round| round.winner.is_some()) {
+        for (total, stats) in totals.iter_mut().zip(&round.players) {
+            total.add(stats);
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod test_analysis {
+    use super::*;
+    use game_core::{replay::Recorder, FalseStart, Rule, TICK_HZ};
+
+    fn at(ms: u32) -> Instant {
+        Instant::from_ticks(ms * TICK_HZ / 1000)
+    }
+
+    fn round_events(go: u32) -> Vec<Event> {
+        vec![
+            Event::RoundStart {
+                at: at(go - 3000),
+                settings: Settings::new(),
+                score: Score::new(),
+            },
+            // a false start
+            Event::Press {
+                at: at(go - 500),
+                player: Players::B,
+            },
+            Event::Go { at: at(go) },
+            Event::Press {
+                at: at(go + 250),
+                player: Players::A,
+            },
+            Event::Step {
+                at: at(go + 250),
+                position: 23,
+            },
+            Event::Press {
+                at: at(go + 500),
+                player: Players::B,
+            },
+            Event::Press {
+                at: at(go + 750),
+                player: Players::A,
+            },
+            Event::Step {
+                at: at(go + 500),
+                position: 22,
+            },
+            Event::Step {
+                at: at(go + 750),
+                position: 23,
+            },
+            Event::Step {
+                at: at(go + 1000),
+                position: 22,
+            },
+            // the dot holds on a tie of the tap rate
+            Event::Step {
+                at: at(go + 1250),
+                position: 22,
+            },
+            Event::Result {
+                at: at(go + 2000),
+                winner: Players::A,
+                score: Score { a: 1, b: 0 },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_rounds() {
+        // joining mid-round
+        let mut events = vec![Event::Step {
+            at: at(0),
+            position: 3,
+        }];
+        events.extend(round_events(4000));
+        events.extend(round_events(10000));
+        // the stream ends mid-round
+        events.extend(round_events(20000)[..4].iter().copied());
+        let rounds = rounds(events);
+        assert_eq!(rounds.len(), 3);
+        assert_eq!(rounds[0].presses.len(), 3);
+        assert_eq!(rounds[2].result, None);
+
+        let stats = rounds[0].stats();
+        assert_eq!(stats.winner, Some(Players::A));
+        assert_eq!(stats.time_to_win_ms, Some(2000));
+        let [a, b] = stats.players;
+        assert_eq!((a.taps, b.taps), (2, 1));
+        assert_eq!(a.taps_per_sec(), Some(1.0));
+        assert_eq!(a.mean_reaction_ms(), Some(250));
+        assert_eq!(b.mean_reaction_ms(), Some(500));
+        assert_eq!((a.held, b.held), (3, 1));
+        assert_eq!(a.steps, 4);
+        assert_eq!(a.held_share(), Some(0.75));
+
+        let all: Vec<_> = rounds.iter().map(Round::stats).collect();
+        let [a, b] = totals(&all);
+        assert_eq!((a.wins, b.wins), (2, 0));
+        assert_eq!(a.mean_win_ms(), Some(2000));
+        assert_eq!(b.mean_win_ms(), None);
+        assert_eq!(a.taps, 4);
+        assert_eq!(a.mean_reaction_ms(), Some(250));
+        assert_eq!(a.taps_per_sec(), Some(1.0));
+    }
+
+    #[test]
+    fn test_log_events() {
+        let settings = Settings {
+            false_start: FalseStart::Penalty,
+            ..Settings::new()
+        };
+        let mut recorder = Recorder::<256>::new();
+        recorder.start(&settings);
+        // the countdown is 3 seconds
+        let go = 3 * TICK_HZ;
+        for tick in 1..go + 400 {
+            recorder.tick();
+            if tick == 1 {
+                recorder.random(0);
+            }
+            if tick > go && tick % 8 == 0 {
+                recorder.press(Players::A, false);
+            }
+        }
+        let events = log_events(recorder.log()).unwrap();
+        assert_eq!(
+            events[0],
+            Event::RoundStart {
+                at: Instant::from_ticks(0),
+                settings,
+                score: Score::new(),
+            }
+        );
+        let rounds = rounds(events);
+        assert!(rounds[0].go.is_some());
+        assert_eq!(rounds.len(), 1);
+        let stats = rounds[0].stats();
+        assert_eq!(stats.winner, Some(Players::A));
+        let [a, b] = stats.players;
+        assert!(a.taps > 10 && b.taps == 0);
+        assert!(a.held > 20 && b.held == 0);
+        assert_eq!(a.held_share(), Some(1.0));
+    }
+
+    #[test]
+    fn test_unfinished_log() {
+        let settings = Settings {
+            rule: Rule::TapRate,
+            ..Settings::new()
+        };
+        let mut recorder = Recorder::<256>::new();
+        recorder.start(&settings);
+        let go = 3 * TICK_HZ;
+        for tick in 1..go + 200 {
+            recorder.tick();
+            match tick {
+                1 => recorder.random(0),
+                // a step towards A, then ties
+                tick if tick == go + 10 => recorder.press(Players::A, false),
+                tick if tick == go + 100 || tick == go + 190 => {
+                    recorder.press(Players::A, false);
+                    recorder.press(Players::B, false);
+                }
+                _ => (),
+            }
+        }
+        let events = log_events(recorder.log()).unwrap();
+        let rounds = rounds(events);
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].result, None);
+        // the presses after the last step
+        assert_eq!(rounds[0].presses.len(), 5);
+        let stats = rounds[0].stats();
+        let [a, b] = stats.players;
+        assert_eq!((a.taps, b.taps), (3, 2));
+        assert_eq!((a.held, a.steps), (1, 1));
+        assert_eq!(b.held, 0);
+    }
+}
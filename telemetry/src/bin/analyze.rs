@@ -0,0 +1,73 @@
+//! Analyses a telemetry stream saved by `telemetry --save`, or the match
+//! log of a round, offline.
+//!
+//! `analyze <file> [--format <human|csv|json>] [--replay]` prints the
+//! taps per second, the reaction time to the start signal, the time to
+//! win and how often each player held the direction of the dot, for each
+//! round and over all of them. `--replay` draws the steps of the dot on
+//! the matrix instead.
+use game_core::{replay::LogError, telemetry::Event};
+use std::{
+    env, fs,
+    io::{self, Write},
+};
+use telemetry::{log_events, replay, report, rounds, Format, Reader, Round};
+
+const USAGE: &str = "usage: analyze <file> [--format <human|csv|json>] [--replay]";
+
+fn main() -> io::Result<()> {
+    let usage = || io::Error::new(io::ErrorKind::InvalidInput, USAGE);
+    let mut path = None;
+    let mut format = Format::Human;
+    let mut replaying = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = args
+                    .next()
+                    .and_then(|name| Format::from_name(&name))
+                    .ok_or_else(usage)?
+            }
+            "--replay" => replaying = true,
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => return Err(usage()),
+        }
+    }
+    let bytes = fs::read(path.ok_or_else(usage)?)?;
+    let rounds = rounds(events(&bytes)?);
+    let mut stdout = io::stdout().lock();
+    if replaying {
+        for (index, round) in rounds.iter().enumerate() {
+            writeln!(stdout, "round {}", index + 1)?;
+            replay(round, &mut stdout)?;
+        }
+        return Ok(());
+    }
+    let stats: Vec<_> = rounds.iter().map(Round::stats).collect();
+    report(&stats, format, &mut stdout)
+}
+
+/// the events of a match log, or else of a telemetry stream,
+/// skipping its bad frames.
+fn events(bytes: &[u8]) -> io::Result<Vec<Event>> {
+    let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}"));
+    match log_events(bytes) {
+        Ok(events) => return Ok(events),
+        Err(LogError::Header) => (),
+        Err(e) => return Err(invalid(e)),
+    }
+    let mut events = vec![];
+    let mut bad = 0;
+    for event in Reader::new(bytes) {
+        match event {
+            Ok(event) => events.push(event),
+            Err(telemetry::Error::Frame(_)) => bad += 1,
+            Err(telemetry::Error::Io(e)) => return Err(e),
+        }
+    }
+    if bad > 0 {
+        eprintln!("skipped {bad} bad frames");
+    }
+    Ok(events)
+}
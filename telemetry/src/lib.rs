@@ -0,0 +1,200 @@
+//! Host side decoder of the telemetry streamed by the board over its
+//! serial port, see [`game_core::telemetry`] for the format, and the
+//! metrics of the rounds it, or a match log, tells.
+use game_core::{
+    spiral_px,
+    telemetry::{Decoder, Event, FrameError},
+    Duration,
+};
+use std::{
+    fmt,
+    io::{self, Read},
+    ops::Range,
+};
+
+mod analysis;
+mod report;
+pub use analysis::*;
+pub use report::*;
+
+/// bytes read from the stream at once.
+const CHUNK: usize = 256;
+
+/// Reading the stream failed.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// a malformed frame, the stream goes on with the next one.
+    Frame(FrameError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Frame(e) => write!(f, "bad frame: {e:?}"),
+        }
+    }
+}
+
+/// Events of a serial port, or of a file it was saved to.
+pub struct Reader<R> {
+    input: R,
+    decoder: Decoder,
+    buf: [u8; CHUNK],
+    /// bytes of `buf` not decoded yet.
+    pending: Range<usize>,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            decoder: Decoder::new(),
+            buf: [0; CHUNK],
+            pending: 0..0,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Event, Error>;
+
+    /// the next event, or bad frame, blocking on the input until the
+    /// end of the stream.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for index in self.pending.by_ref() {
+                if let Some(frame) = self.decoder.push(self.buf[index]) {
+                    return Some(frame.map_err(Error::Frame));
+                }
+            }
+            match self.input.read(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(len) => self.pending = 0..len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Some(Err(Error::Io(e))),
+            }
+        }
+    }
+}
+
+/// An event as a line of the log: the time on the clock of the board
+/// in seconds, and what happened.
+pub struct Line<'a>(pub &'a Event);
+
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ms = Duration::from_ticks(self.0.at().ticks()).as_ms();
+        write!(f, "{:>6}.{:03}  ", ms / 1000, ms % 1000)?;
+        match self.0 {
+            Event::RoundStart {
+                settings, score, ..
+            } => write!(
+                f,
+                "round start  {}-{}  {:?} best of {:?}, {:?}, bot {:?}",
+                score.a, score.b, settings.rule, settings.best_of, settings.speed, settings.bot
+            ),
+            Event::Go { .. } => write!(f, "go"),
+            Event::Step { position, .. } => {
+                let (row, col) = spiral_px(*position).unwrap_or_default();
+                write!(f, "dot {position} ({row}, {col})")
+            }
+            Event::Press { player, .. } => write!(f, "press {}", player.letter()),
+            Event::Result { winner, score, .. } => {
+                write!(f, "{} wins  {}-{}", winner.letter(), score.a, score.b)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_reader {
+    use super::*;
+    use game_core::{telemetry::MAX_FRAME, Instant, Players, Score, Settings};
+
+    /// hands out the stream a few bytes at a time, as a serial port.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn stream(events: &[Event]) -> Vec<u8> {
+        let mut stream = vec![];
+        for event in events {
+            let mut frame = [0; MAX_FRAME];
+            let len = event.encode(&mut frame);
+            stream.extend_from_slice(&frame[..len]);
+        }
+        stream
+    }
+
+    fn round() -> Vec<Event> {
+        let at = Instant::from_ticks;
+        vec![
+            Event::RoundStart {
+                at: at(256),
+                settings: Settings::new(),
+                score: Score::new(),
+            },
+            Event::Press {
+                at: at(1100),
+                player: Players::A,
+            },
+            Event::Step {
+                at: at(1102),
+                position: 23,
+            },
+            Event::Result {
+                at: at(1300),
+                winner: Players::A,
+                score: Score { a: 1, b: 0 },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_read() {
+        let events = round();
+        let bytes = stream(&events);
+        let read: Vec<_> = Reader::new(Trickle(&bytes)).map(Result::unwrap).collect();
+        assert_eq!(read, events);
+    }
+
+    #[test]
+    fn test_resync() {
+        let events = round();
+        // joining mid-frame
+        let mut bytes = vec![0x42, 0x17, 0];
+        bytes.extend(stream(&events[..1]));
+        // a press with a flipped bit in its instant
+        let mut press = stream(&events[1..2]);
+        press[3] ^= 0x10;
+        bytes.extend(press);
+        bytes.extend(stream(&events[1..]));
+        let read: Vec<_> = Reader::new(&bytes[..]).collect();
+        assert_eq!(read.len(), 6);
+        assert!(matches!(read[0], Err(Error::Frame(FrameError::Encoding))));
+        assert!(matches!(read[2], Err(Error::Frame(FrameError::Crc))));
+        let good: Vec<_> = read.into_iter().filter_map(Result::ok).collect();
+        assert_eq!(good, events);
+    }
+
+    #[test]
+    fn test_lines() {
+        let lines: Vec<_> = round().iter().map(|e| Line(e).to_string()).collect();
+        assert_eq!(
+            lines[0],
+            "     1.000  round start  0-0  LastPress best of One, Normal, bot None"
+        );
+        assert_eq!(lines[1], "     4.296  press A");
+        assert_eq!(lines[2], "     4.304  dot 23 (0, 1)");
+        assert_eq!(lines[3], "     5.078  A wins  1-0");
+    }
+}
@@ -0,0 +1,65 @@
+//! Logs the telemetry of a board, e.g. through a tournament.
+//!
+//! `telemetry [<port|file>] [--save <file>]` prints the events read from
+//! the serial port of the board, a file saved before, or stdin, one per
+//! line. With `--save`, the raw stream is also appended to the file,
+//! to read again later.
+//!
+//! The port must be set up first, e.g. `stty -F /dev/ttyACM0 115200 raw`.
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+};
+use telemetry::{Line, Reader};
+
+const USAGE: &str = "usage: telemetry [<port|file>] [--save <file>]";
+
+/// Copies the bytes read to a file.
+struct Tee<R> {
+    input: R,
+    save: File,
+}
+
+impl<R: Read> Read for Tee<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.input.read(buf)?;
+        self.save.write_all(&buf[..len])?;
+        Ok(len)
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut path = None;
+    let mut save = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = args.next(),
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, USAGE)),
+        }
+    }
+    let input: Box<dyn Read> = match path {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(io::stdin()),
+    };
+    let input: Box<dyn Read> = match save {
+        Some(save) => Box::new(Tee {
+            input,
+            save: OpenOptions::new().create(true).append(true).open(save)?,
+        }),
+        None => input,
+    };
+    let mut stdout = io::stdout();
+    for event in Reader::new(input) {
+        match event {
+            Ok(event) => writeln!(stdout, "{}", Line(&event))?,
+            // keep logging after a corrupted frame
+            Err(e @ telemetry::Error::Frame(_)) => eprintln!("{e}"),
+            Err(telemetry::Error::Io(e)) => return Err(e),
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
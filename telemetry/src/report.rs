@@ -0,0 +1,300 @@
+//! Reports of the metrics, for people or for spreadsheets and scripts,
+//! and the replay of the dot.
+use crate::{totals, PlayerStats, Round, RoundStats};
+use game_core::{spiral_px, DotState, Players};
+use std::{
+    cmp::Ordering,
+    io::{self, Write},
+};
+
+/// Output format of a report.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Human,
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(Format::Human),
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+const PLAYERS: [Players; 2] = [Players::A, Players::B];
+const CSV_HEADER: &str =
+    "round,player,wins,taps,taps_per_sec,reaction_ms,time_to_win_ms,held_steps,steps";
+
+/// the metrics of each round, and of each player over all rounds.
+pub fn report(rounds: &[RoundStats], format: Format, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Human => human(rounds, out),
+        Format::Csv => csv(rounds, out),
+        Format::Json => json(rounds, out),
+    }
+}
+
+fn human(rounds: &[RoundStats], out: &mut impl Write) -> io::Result<()> {
+    let ms = |ms: Option<u32>| ms.map_or("-".to_string(), |ms| format!("{ms} ms"));
+    let rate = |rate: Option<f64>| rate.map_or("-".to_string(), |rate| format!("{rate:.2}"));
+    let share =
+        |share: Option<f64>| share.map_or("-".to_string(), |s| format!("{:.0}%", s * 100.0));
+    for (index, round) in rounds.iter().enumerate() {
+        let outcome = match (round.winner, round.time_to_win_ms) {
+            (Some(winner), Some(ms)) => format!("{} wins in {ms} ms", winner.letter()),
+            (Some(winner), None) => format!("{} wins by false start", winner.letter()),
+            (None, _) => "unfinished".to_string(),
+        };
+        let settings = &round.settings;
+        writeln!(
+            out,
+            "round {}: {outcome} ({:?}, {:?})",
+            index + 1,
+            settings.rule,
+            settings.speed
+        )?;
+        writeln!(out, "  player  taps  taps/s  reaction  held")?;
+        for (player, stats) in PLAYERS.iter().zip(&round.players) {
+            writeln!(
+                out,
+                "  {:<6} {:>5} {:>7} {:>9} {:>5}",
+                player.letter(),
+                stats.taps,
+                rate(stats.taps_per_sec()),
+                ms(stats.mean_reaction_ms()),
+                share(stats.held_share())
+            )?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "total: {} rounds", rounds.len())?;
+    writeln!(
+        out,
+        "  player  wins  taps  taps/s  reaction  time to win  held"
+    )?;
+    for (player, stats) in PLAYERS.iter().zip(&totals(rounds)) {
+        writeln!(
+            out,
+            "  {:<6} {:>5} {:>5} {:>7} {:>9} {:>12} {:>5}",
+            player.letter(),
+            stats.wins,
+            stats.taps,
+            rate(stats.taps_per_sec()),
+            ms(stats.mean_reaction_ms()),
+            ms(stats.mean_win_ms()),
+            share(stats.held_share())
+        )?;
+    }
+    Ok(())
+}
+
+/// a row per player of each round, and of the totals.
+fn csv(rounds: &[RoundStats], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for (index, round) in rounds.iter().enumerate() {
+        for (player, stats) in PLAYERS.iter().zip(&round.players) {
+            csv_row(&(index + 1).to_string(), *player, stats, out)?;
+        }
+    }
+    for (player, stats) in PLAYERS.iter().zip(&totals(rounds)) {
+        csv_row("total", *player, stats, out)?;
+    }
+    Ok(())
+}
+
+fn csv_row(
+    round: &str,
+    player: Players,
+    stats: &PlayerStats,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let cell = |value: Option<String>| value.unwrap_or_default();
+    writeln!(
+        out,
+        "{round},{},{},{},{},{},{},{},{}",
+        player.letter(),
+        stats.wins,
+        stats.taps,
+        cell(stats.taps_per_sec().map(|rate| format!("{rate:.3}"))),
+        cell(stats.mean_reaction_ms().map(|ms| ms.to_string())),
+        cell(stats.mean_win_ms().map(|ms| ms.to_string())),
+        stats.held,
+        stats.steps
+    )
+}
+
+fn json(rounds: &[RoundStats], out: &mut impl Write) -> io::Result<()> {
+    let or_null = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+    let players = |stats: &[PlayerStats; 2]| {
+        let players: Vec<_> = PLAYERS
+            .iter()
+            .zip(stats)
+            .map(|(player, stats)| {
+                format!(
+                    "\"{}\":{{\"wins\":{},\"taps\":{},\"taps_per_sec\":{},\"reaction_ms\":{},\
+                     \"time_to_win_ms\":{},\"held_steps\":{},\"steps\":{},\"held_share\":{}}}",
+                    player.letter(),
+                    stats.wins,
+                    stats.taps,
+                    or_null(stats.taps_per_sec().map(|rate| format!("{rate:.3}"))),
+                    or_null(stats.mean_reaction_ms().map(|ms| ms.to_string())),
+                    or_null(stats.mean_win_ms().map(|ms| ms.to_string())),
+                    stats.held,
+                    stats.steps,
+                    or_null(stats.held_share().map(|share| format!("{share:.3}"))),
+                )
+            })
+            .collect();
+        format!("{{{}}}", players.join(","))
+    };
+    let rounds_json: Vec<_> = rounds
+        .iter()
+        .enumerate()
+        .map(|(index, round)| {
+            format!(
+                "{{\"round\":{},\"winner\":{},\"time_to_win_ms\":{},\"players\":{}}}",
+                index + 1,
+                or_null(
+                    round
+                        .winner
+                        .map(|winner| format!("\"{}\"", winner.letter()))
+                ),
+                or_null(round.time_to_win_ms.map(|ms| ms.to_string())),
+                players(&round.players)
+            )
+        })
+        .collect();
+    writeln!(
+        out,
+        "{{\"rounds\":[{}],\"totals\":{}}}",
+        rounds_json.join(","),
+        players(&totals(rounds))
+    )
+}
+
+/// each step of the dot on the matrix, timed from the start signal,
+/// with the previous position as `o`, and the side it moves towards.
+pub fn replay(round: &Round, out: &mut impl Write) -> io::Result<()> {
+    let start = round.go.unwrap_or(round.start);
+    let mut previous = DotState::new().position();
+    for (at, position) in &round.steps {
+        let ms = at.since(start).as_ms();
+        let towards = match position.cmp(&previous) {
+            Ordering::Less => "< A",
+            Ordering::Greater => "B >",
+            Ordering::Equal => "",
+        };
+        writeln!(
+            out,
+            "{:>4}.{:03}  dot {position:>2}  {towards}",
+            ms / 1000,
+            ms % 1000
+        )?;
+        let (dot, trail) = (spiral_px(*position), spiral_px(previous));
+        for row in 0..5 {
+            let cells: Vec<_> = (0..5)
+                .map(|col| match Some((row, col)) {
+                    px if px == dot => "@",
+                    px if px == trail => "o",
+                    _ => ".",
+                })
+                .collect();
+            writeln!(out, "  {}", cells.join(" "))?;
+        }
+        previous = *position;
+    }
+    if let Some((at, winner)) = round.result {
+        let ms = at.since(start).as_ms();
+        writeln!(
+            out,
+            "{:>4}.{:03}  {} wins",
+            ms / 1000,
+            ms % 1000,
+            winner.letter()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_report {
+    use super::*;
+    use game_core::{Instant, Settings};
+
+    fn round() -> Round {
+        let at = Instant::from_ticks;
+        Round {
+            settings: Settings::new(),
+            start: at(0),
+            go: Some(at(768)),
+            presses: vec![(at(832), Players::A), (at(900), Players::B)],
+            steps: vec![(at(840), 23), (at(856), 22)],
+            result: Some((at(1024), Players::A)),
+        }
+    }
+
+    fn output(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut out = vec![];
+        write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_formats() {
+        let forfeit = Round {
+            go: None,
+            presses: vec![],
+            steps: vec![],
+            ..round()
+        };
+        let rounds = [round().stats(), forfeit.stats()];
+        let human = output(|out| report(&rounds, Format::Human, out));
+        assert!(human.starts_with("round 1: A wins in 1000 ms (LastPress, Normal)\n"));
+        assert!(human.contains("round 2: A wins by false start"));
+        assert!(human.contains("  A          1    1.00    250 ms  100%\n"));
+        assert!(human.contains("total: 2 rounds\n"));
+
+        let csv = output(|out| report(&rounds, Format::Csv, out));
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1], "1,A,1,1,1.000,250,1000,2,2");
+        assert_eq!(lines[2], "1,B,0,1,1.000,515,,0,2");
+        assert_eq!(lines[3], "2,A,1,0,,,,0,0");
+        assert_eq!(lines[5], "total,A,2,1,1.000,250,1000,2,2");
+
+        let json = output(|out| report(&rounds[..1], Format::Json, out));
+        assert_eq!(
+            json,
+            "{\"rounds\":[{\"round\":1,\"winner\":\"A\",\"time_to_win_ms\":1000,\"players\":{\
+             \"A\":{\"wins\":1,\"taps\":1,\"taps_per_sec\":1.000,\"reaction_ms\":250,\
+             \"time_to_win_ms\":1000,\"held_steps\":2,\"steps\":2,\"held_share\":1.000},\
+             \"B\":{\"wins\":0,\"taps\":1,\"taps_per_sec\":1.000,\"reaction_ms\":515,\
+             \"time_to_win_ms\":null,\"held_steps\":0,\"steps\":2,\"held_share\":0.000}}}],\
+             \"totals\":{\
+             \"A\":{\"wins\":1,\"taps\":1,\"taps_per_sec\":1.000,\"reaction_ms\":250,\
+             \"time_to_win_ms\":1000,\"held_steps\":2,\"steps\":2,\"held_share\":1.000},\
+             \"B\":{\"wins\":0,\"taps\":1,\"taps_per_sec\":1.000,\"reaction_ms\":515,\
+             \"time_to_win_ms\":null,\"held_steps\":0,\"steps\":2,\"held_share\":0.000}}}\n"
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let replay = output(|out| replay(&round(), out));
+        let lines: Vec<_> = replay.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "   0.281  dot 23  < A");
+        // from 24 at (0, 2) to 23 at (0, 1)
+        assert_eq!(lines[1], "  . @ o . .");
+        assert_eq!(lines[3], "  . . . . .");
+        assert_eq!(lines[6], "   0.343  dot 22  < A");
+        assert_eq!(lines[7], "  @ o . . .");
+        assert_eq!(lines[12], "   1.000  A wins");
+    }
+}
@@ -1,7 +1,8 @@
 MEMORY
 {
   /* NOTE K = KiBi = 1024 bytes */
-  FLASH : ORIGIN = 0x00000000, LENGTH = 512K
+  /* the last 16K keep the settings and stats, see src/flash.rs */
+  FLASH : ORIGIN = 0x00000000, LENGTH = 496K
   RAM : ORIGIN = 0x20000000, LENGTH = 128K
 }
 
@@ -0,0 +1,62 @@
+//! Remote board of a player, sending the presses of its buttons over
+//! the radio to the board running the game, built with the `radio`
+//! feature.
+//!
+//! A and B are the same button. The top left LED lights once paired as
+//! player A, the top right one as player B.
+#![no_main]
+#![no_std]
+use cortex_m_rt::entry;
+use game_core::{link::Remote, ButtonEdge, Debouncer, Instant, Players};
+use microbit::{
+    hal::{
+        clocks::{ExternalOscillator, Internal, LfOscStarted},
+        prelude::{InputPin as _, OutputPin as _},
+        Clocks, Rtc,
+    },
+    Board,
+};
+use panic_halt as _;
+
+#[path = "../radio.rs"]
+mod radio;
+use radio::*;
+
+/// 256HZ (32768 / 256 - 1), as the game.
+const RTC_PRESCALER: u32 = 127;
+
+#[entry]
+fn main() -> ! {
+    let board = Board::take().unwrap();
+    let clocks = Clocks::new(board.CLOCK)
+        .enable_ext_hfosc()
+        .set_lfclk_src_rc()
+        .start_lfclk();
+    let clocks = cortex_m::singleton!(
+        : Clocks<ExternalOscillator, Internal, LfOscStarted> = clocks
+    )
+    .unwrap();
+    let rtc = Rtc::new(board.RTC0, RTC_PRESCALER).unwrap();
+    rtc.enable_counter();
+    let id = board.FICR.deviceid[0].read().bits();
+    let mut remote = Remote::new(RadioLink::new(board.RADIO, board.TIMER2, clocks), id);
+    let mut button = Debouncer::default();
+    let (a, b) = (board.buttons.button_a, board.buttons.button_b);
+    let mut pins = board.display_pins;
+    pins.row1.set_high().unwrap();
+    loop {
+        let now = Instant::from_ticks(rtc.get_counter());
+        let down = a.is_low().unwrap() || b.is_low().unwrap();
+        if let Some(edge) = button.edge(down, now) {
+            remote.edge(edge == ButtonEdge::Pressed, now);
+        }
+        remote.poll(now);
+        let player = remote.player();
+        pins.col1
+            .set_state((player != Some(Players::A)).into())
+            .unwrap();
+        pins.col5
+            .set_state((player != Some(Players::B)).into())
+            .unwrap();
+    }
+}
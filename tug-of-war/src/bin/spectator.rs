@@ -0,0 +1,103 @@
+//! Spectator board, mirroring the match broadcast over the radio by the
+//! board running the game, built with the `radio` feature.
+#![no_main]
+#![no_std]
+use cortex_m_rt::entry;
+use embed_mutex::Mutex;
+use game_core::{
+    broadcast::{Snapshot, SIGNAL_TIMEOUT},
+    link::{Transport as _, MAX_FRAME},
+    Frame, Instant, LedMatrix as _,
+};
+use microbit::{
+    gpio::{NUM_COLS, NUM_ROWS},
+    hal::{
+        clocks::{ExternalOscillator, Internal, LfOscStarted},
+        gpio::{Output, Pin, PushPull},
+        Clocks, Rtc,
+    },
+    pac::{interrupt, NVIC},
+    Board,
+};
+use panic_halt as _;
+
+#[path = "../display.rs"]
+mod display;
+#[path = "../radio.rs"]
+mod radio;
+use display::*;
+use radio::*;
+
+/// the matrix, scanned by the timer interrupt while the board sleeps.
+static DISPLAY: Mutex<LedDriver> = Mutex::new_uinit();
+
+/// 256HZ (32768 / 256 - 1), as the game.
+const RTC_PRESCALER: u32 = 127;
+
+type DisplayPinsArray = (
+    [Pin<Output<PushPull>>; NUM_COLS],
+    [Pin<Output<PushPull>>; NUM_ROWS],
+);
+
+#[entry]
+fn main() -> ! {
+    let board = Board::take().unwrap();
+    let clocks = Clocks::new(board.CLOCK)
+        .enable_ext_hfosc()
+        .set_lfclk_src_rc()
+        .start_lfclk();
+    let clocks = cortex_m::singleton!(
+        : Clocks<ExternalOscillator, Internal, LfOscStarted> = clocks
+    )
+    .unwrap();
+    let rtc = Rtc::new(board.RTC0, RTC_PRESCALER).unwrap();
+    rtc.enable_counter();
+    let mut link = RadioLink::new(board.RADIO, board.TIMER2, clocks);
+    let display = LedDriver::new(board.display_pins.degrade(), board.TIMER1);
+    cortex_m::interrupt::free(|cs| DISPLAY.init(cs, display));
+    unsafe {
+        NVIC::unmask(interrupt::TIMER1);
+        NVIC::unmask(interrupt::RADIO);
+    }
+    // the last snapshot, and when it was received
+    let mut last: Option<(Snapshot, Instant)> = None;
+    let mut buf = [0; MAX_FRAME];
+    loop {
+        let now = Instant::from_ticks(rtc.get_counter());
+        let received = link.recv(&mut buf);
+        if let Some(snapshot) = received.and_then(|len| Snapshot::decode(&buf[..len]).ok()) {
+            last = Some((snapshot, now));
+        }
+        let mut frame = Frame::new();
+        match &last {
+            Some((snapshot, at)) if now.since(*at) < SIGNAL_TIMEOUT => snapshot.draw(&mut frame),
+            _ => (),
+        }
+        cortex_m::interrupt::free(|cs| {
+            if let Some(mut display) = DISPLAY.try_lock(cs) {
+                display.show(&frame);
+            }
+        });
+        // until a frame is received, or the next scan of the matrix
+        cortex_m::asm::wfi();
+    }
+}
+
+/// interrupt of the radio, waking the main loop once a frame is
+/// received, which the next `recv` reads.
+#[interrupt]
+fn RADIO() {
+    unsafe { &*microbit::pac::RADIO::ptr() }
+        .intenclr
+        .write(|w| w.end().clear());
+}
+
+/// interrupt for multiplexing the display.
+#[interrupt]
+fn TIMER1() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(mut display) = DISPLAY.try_lock(cs) {
+            display.handle_interrupt();
+        }
+    });
+}
@@ -1,95 +1,195 @@
+use crate::TouchLogo;
+use game_core::{
+    Bot, ButtonInput, ButtonLatch, Difficulty, Gesture, InputEvent, Instant, LatchedInput,
+    LogoAction, Players, RandomSource,
+};
 use microbit::hal::{
-    gpio::{Floating, Input, Pin},
-    gpiote::Gpiote,
+    gpio::{Input, Pin, PullUp},
+    gpiote::{Gpiote, GpioteChannel},
     prelude::InputPin as _,
 };
 
-const LAST_BUTTON_MASK: u8 = 0b0000_0001;
-const BOTH_AB_MASK: u8 = 0b0000_0010;
+/// A button pressing for a team, low when pressed.
+pub struct Button {
+    pin: Pin<Input<PullUp>>,
+    player: Players,
+    /// the button of the team, 0 for the one on the board.
+    member: u8,
+}
+
+impl Button {
+    pub fn new(pin: Pin<Input<PullUp>>, player: Players, member: u8) -> Self {
+        Self {
+            pin,
+            player,
+            member,
+        }
+    }
+}
 
-/// Buttons
-pub struct ButtonState {
-    state: u8,
-    pub button_a: Pin<Input<Floating>>,
-    pub button_b: Pin<Input<Floating>>,
+/// Buttons, each on the GPIOTE channel of its index.
+pub struct ButtonState<const N: usize> {
+    latch: ButtonLatch,
+    /// computer opponent pressing B.
+    bot: Bot,
+    buttons: [Button; N],
     gpiote: Gpiote,
+    logo: TouchLogo,
 }
 
-impl ButtonState {
-    pub fn new(
-        button_a: Pin<Input<Floating>>,
-        button_b: Pin<Input<Floating>>,
-        gpiote: Gpiote,
-    ) -> Self {
-        gpiote
-            .channel0()
-            .input_pin(&button_a)
-            .hi_to_lo()
-            .enable_interrupt();
-        gpiote
-            .channel1()
-            .input_pin(&button_b)
-            .hi_to_lo()
-            .enable_interrupt();
+impl<const N: usize> ButtonState<N> {
+    pub fn new(buttons: [Button; N], gpiote: Gpiote, logo: TouchLogo) -> Self {
+        const { assert!(N <= 8, "8 GPIOTE channels") };
+        for (index, button) in buttons.iter().enumerate() {
+            channel(&gpiote, index)
+                .input_pin(&button.pin)
+                .toggle()
+                .enable_interrupt();
+        }
         Self {
-            state: 0,
-            button_a,
-            button_b,
+            latch: ButtonLatch::new(),
+            bot: Bot::new(),
+            buttons,
             gpiote,
+            logo,
         }
     }
 
-    #[inline]
-    pub fn both_pressed(&self) -> bool {
-        self.state & BOTH_AB_MASK != 0
+    /// returns the edge of a button, read at `now`.
+    pub fn handle_interrupt(&mut self, now: Instant) -> Option<InputEvent> {
+        let (index, button) = self
+            .buttons
+            .iter()
+            .enumerate()
+            .find(|(index, _)| channel(&self.gpiote, *index).is_event_triggered())?;
+        channel(&self.gpiote, index).reset_events();
+        let down = button.pin.is_low().unwrap();
+        let (player, member) = (button.player, button.member);
+        crate::debug::info!("button {}{} {}", player.letter(), member, down);
+        Some(if down {
+            InputEvent::ButtonDown {
+                player,
+                member,
+                at: now,
+            }
+        } else {
+            InputEvent::ButtonUp {
+                player,
+                member,
+                at: now,
+            }
+        })
     }
 
+    /// returns the touch or release of the logo, sampled at `now`,
+    /// call once per tick.
     #[inline]
-    pub fn last_a(&self) -> bool {
-        self.state & LAST_BUTTON_MASK != 0
+    pub fn poll_logo(&mut self, now: Instant) -> Option<InputEvent> {
+        self.logo.poll(now)
     }
 
+    /// what a touch of the logo does.
     #[inline]
-    pub fn reset(&mut self) {
-        self.state = 0
+    pub fn bind_logo(&mut self, action: LogoAction) {
+        self.latch.bind_logo(action)
     }
 
+    /// apply an event of `handle_interrupt`, once per tick.
     #[inline]
-    pub fn set_last_a(&mut self) {
-        self.state |= LAST_BUTTON_MASK;
+    pub fn handle_event(&mut self, event: &InputEvent) -> Option<LatchedInput> {
+        self.latch.handle_event(event)
     }
 
+    /// settle the bouncing buttons, and detect long presses,
+    /// after the events of the tick.
     #[inline]
-    fn set_last_b(&mut self) {
-        self.state &= !LAST_BUTTON_MASK;
+    pub fn poll(&mut self, now: Instant) -> Option<LatchedInput> {
+        self.latch.poll(now)
     }
 
+    /// the gesture the game will take.
     #[inline]
-    fn set_both_pressed(&mut self) {
-        self.state |= BOTH_AB_MASK;
-    }
-
-    pub fn handle_interrupt(&mut self) {
-        let button_a = self.gpiote.channel0();
-        if button_a.is_event_triggered() {
-            button_a.reset_events();
-            crate::debug::info!("button A");
-            self.set_last_a();
-            if self.button_b.is_low().unwrap() {
-                crate::debug::info!("button A + B");
-                self.set_both_pressed();
-            }
+    pub fn gesture(&self) -> Option<Gesture> {
+        self.latch.gesture()
+    }
+
+    /// let the computer opponent press B, call once per tick.
+    ///
+    /// returns the button pressed, and whether the other button is held.
+    pub fn poll_bot(
+        &mut self,
+        bot: Option<Difficulty>,
+        playing: bool,
+        rng: &mut impl RandomSource,
+    ) -> Option<(Players, bool)> {
+        let difficulty = bot?;
+        if !playing {
+            self.bot.reset(difficulty);
+            return None;
+        }
+        if self.bot.poll(difficulty, rng) {
+            Some(self.press_b())
         } else {
-            let button_b = self.gpiote.channel1();
-            if button_b.is_event_triggered() {
-                button_b.reset_events();
-                crate::debug::info!("button B");
-                self.set_last_b();
-                if self.button_a.is_low().unwrap() {
-                    crate::debug::info!("button A + B");
-                    self.set_both_pressed();
-                }
-            }
+            None
         }
     }
+
+    /// like a press on the board, the press of the bot never presses
+    /// both buttons, as the replay does not.
+    #[inline]
+    fn press_b(&mut self) -> (Players, bool) {
+        self.latch.press_b(false);
+        (Players::B, self.latch.a_held())
+    }
+}
+
+impl<const N: usize> ButtonInput for ButtonState<N> {
+    #[inline]
+    fn both_pressed(&self) -> bool {
+        self.latch.both_pressed()
+    }
+
+    #[inline]
+    fn last_a(&self) -> bool {
+        self.latch.last_a()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.latch.reset()
+    }
+
+    #[inline]
+    fn set_last_a(&mut self) {
+        self.latch.set_last_a()
+    }
+
+    #[inline]
+    fn take_presses(&mut self) -> (u8, u8) {
+        self.latch.take_presses()
+    }
+
+    #[inline]
+    fn a_held(&self) -> bool {
+        self.latch.a_held()
+    }
+
+    #[inline]
+    fn take_gesture(&mut self) -> Option<Gesture> {
+        self.latch.take_gesture()
+    }
+}
+
+/// GPIOTE channel `index`, below 8.
+fn channel(gpiote: &Gpiote, index: usize) -> GpioteChannel<'_> {
+    match index {
+        0 => gpiote.channel0(),
+        1 => gpiote.channel1(),
+        2 => gpiote.channel2(),
+        3 => gpiote.channel3(),
+        4 => gpiote.channel4(),
+        5 => gpiote.channel5(),
+        6 => gpiote.channel6(),
+        _ => gpiote.channel7(),
+    }
 }
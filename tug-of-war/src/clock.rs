@@ -0,0 +1,130 @@
+//! The RTC driving the game, its timers polled each tick, and the timers
+//! fired by its compare channel.
+use game_core::{Duration, GameClock, GameTimer, Instant, TickAck, TimerMode, Timers, GAME_TIMERS};
+use microbit::{
+    hal::{
+        clocks::{Internal, LfOscStarted},
+        rtc::{RtcCompareReg, RtcInterrupt},
+        Clocks, Rtc,
+    },
+    pac::RTC0,
+};
+
+/// The started clocks, with the crystal oscillator needed by the radio.
+#[cfg(feature = "radio")]
+pub type HfClocks = Clocks<microbit::hal::clocks::ExternalOscillator, Internal, LfOscStarted>;
+/// The started clocks, on the internal oscillator.
+#[cfg(not(feature = "radio"))]
+pub type HfClocks = Clocks<Internal, Internal, LfOscStarted>;
+
+/// Events of the timers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimerEvent {
+    /// save the settings, once they stop changing.
+    SaveSettings,
+}
+
+/// the compare channel firing the timers.
+const TIMER_COMPARE: RtcCompareReg = RtcCompareReg::Compare0;
+const TIMER_INTERRUPT: RtcInterrupt = RtcInterrupt::Compare0;
+/// the compare channel misses a counter value less than 2 ticks ahead.
+const MIN_AHEAD: Duration = Duration::from_ticks(2);
+/// timers running at once, the save of the settings.
+const TIMERS: usize = 1;
+
+/// The 256HZ RTC, ticking the game and counting for the timers.
+pub struct RtcClock {
+    rtc: Rtc<RTC0>,
+    timers: Timers<TimerEvent, TIMERS>,
+    /// timers of the game, polled each tick.
+    game: Timers<GameTimer, GAME_TIMERS>,
+}
+
+impl RtcClock {
+    pub fn new(mut rtc: Rtc<RTC0>) -> Self {
+        rtc.enable_event(TIMER_INTERRUPT);
+        rtc.enable_interrupt(TIMER_INTERRUPT, None);
+        Self {
+            rtc,
+            timers: Timers::new(),
+            game: Timers::new(),
+        }
+    }
+
+    #[inline]
+    pub fn now(&self) -> Instant {
+        Instant::from_ticks(self.rtc.get_counter())
+    }
+
+    /// whether the game tick is due, the interrupt is also
+    /// raised by the timers.
+    #[inline]
+    pub fn is_tick(&self) -> bool {
+        self.rtc.is_event_triggered(RtcInterrupt::Tick)
+    }
+
+    /// fire `event` after `duration`, restarting it if running.
+    pub fn start(&mut self, event: TimerEvent, duration: Duration, mode: TimerMode) {
+        let now = self.now();
+        if self.timers.start(event, now, duration, mode).is_err() {
+            crate::debug::info!("no timer left for {:?}", event);
+        }
+        self.set_compare(now);
+    }
+
+    #[inline]
+    pub fn cancel(&mut self, event: TimerEvent) {
+        self.timers.cancel(event);
+    }
+
+    /// take the event of a due timer, call until `None`.
+    pub fn poll_timers(&mut self) -> Option<TimerEvent> {
+        self.rtc.reset_event(TIMER_INTERRUPT);
+        let now = self.now();
+        let event = self.timers.poll(now);
+        if event.is_none() {
+            self.set_compare(now);
+        }
+        event
+    }
+
+    /// fire the compare event at the next deadline.
+    #[inline]
+    fn set_compare(&mut self, now: Instant) {
+        if let Some(deadline) = self.timers.next_deadline(now) {
+            let deadline = now.after(deadline.since(now).max(MIN_AHEAD));
+            self.rtc.set_compare(TIMER_COMPARE, deadline.ticks()).ok();
+        }
+    }
+}
+
+impl TickAck for RtcClock {
+    #[inline]
+    fn ack_tick(&mut self) {
+        self.rtc.reset_event(RtcInterrupt::Tick);
+    }
+}
+
+impl GameClock for RtcClock {
+    fn start_timer(&mut self, timer: GameTimer, duration: Duration, mode: TimerMode) {
+        let now = self.now();
+        if self.game.start(timer, now, duration, mode).is_err() {
+            crate::debug::info!("no timer left for {:?}", timer);
+        }
+    }
+
+    #[inline]
+    fn cancel_timers(&mut self) {
+        self.game.clear();
+    }
+
+    #[inline]
+    fn poll_timer(&mut self) -> Option<GameTimer> {
+        self.game.poll(self.now())
+    }
+
+    #[inline]
+    fn remaining(&self, timer: GameTimer) -> Option<Duration> {
+        self.game.remaining(timer, self.now())
+    }
+}
@@ -1,32 +1,75 @@
 use crate::DisplayPinsArray;
-use microbit::hal::prelude::OutputPin as _;
+use game_core::{Frame, LedMatrix, MAX_BRIGHTNESS, SIZE};
+use microbit::{
+    hal::{
+        prelude::{_embedded_hal_timer_CountDown as _, OutputPin as _},
+        timer::Periodic,
+        Timer,
+    },
+    pac::TIMER1,
+};
 
-#[inline]
-pub fn display_px((x, y): &(u8, u8), (cols, rows): &mut DisplayPinsArray) {
-    rows[*x as usize].set_high().unwrap();
-    cols[*y as usize].set_low().unwrap();
-}
+/// 1MHZ timer cycles per brightness level, each row is on for
+/// `MAX_BRIGHTNESS` levels, the matrix refreshes at about 200HZ.
+const LEVEL_CYCLES: u32 = 110;
 
-#[inline]
-pub fn undisplay_px((x, y): &(u8, u8), (cols, rows): &mut DisplayPinsArray) {
-    rows[*x as usize].set_low().unwrap();
-    cols[*y as usize].set_high().unwrap();
+/// LED matrix showing a frame, one row at a time.
+///
+/// each row stays on for a slice per brightness level, and a pixel
+/// is turned off after as many slices as its brightness.
+pub struct LedDriver {
+    pins: DisplayPinsArray,
+    timer: Timer<TIMER1, Periodic>,
+    frame: Frame,
+    /// the row currently on.
+    row: u8,
+    /// the brightness level of the current slice.
+    level: u8,
 }
 
-#[inline]
-pub fn display_col(col: u8, col_code: u8, (cols, rows): &mut DisplayPinsArray) {
-    cols[col as usize].set_low().unwrap();
-    IntoIterator::into_iter([0b00001, 0b00010, 0b00100, 0b01000, 0b10000])
-        .zip(rows)
-        .for_each(|(mask, row)| {
-            if mask & col_code as u32 != 0 {
-                row.set_high().unwrap();
+impl LedDriver {
+    /// start scanning a blank frame.
+    pub fn new(pins: DisplayPinsArray, timer: TIMER1) -> Self {
+        let mut timer = Timer::periodic(timer);
+        timer.enable_interrupt();
+        timer.start(LEVEL_CYCLES);
+        Self {
+            pins,
+            timer,
+            frame: Frame::new(),
+            row: 0,
+            level: 0,
+        }
+    }
+
+    /// turn off the pixels dimmer than the next level,
+    /// or move to the next row after the last level.
+    #[inline]
+    pub fn handle_interrupt(&mut self) {
+        // reset the compare event
+        self.timer.wait().ok();
+        let (cols, rows) = &mut self.pins;
+        self.level += 1;
+        if self.level == MAX_BRIGHTNESS {
+            self.level = 0;
+            rows[self.row as usize].set_low().unwrap();
+            self.row = (self.row + 1) % SIZE;
+        }
+        let lit = self.frame.row(self.row, self.level);
+        cols.iter_mut().enumerate().for_each(|(col, pin)| {
+            if lit & (1 << col) != 0 {
+                pin.set_low().unwrap();
+            } else {
+                pin.set_high().unwrap();
             }
-        })
+        });
+        rows[self.row as usize].set_high().unwrap();
+    }
 }
 
-#[inline]
-pub fn undisplay_col(col: u8, (cols, rows): &mut DisplayPinsArray) {
-    cols[col as usize].set_high().unwrap();
-    rows.iter_mut().for_each(|row| row.set_low().unwrap());
+impl LedMatrix for LedDriver {
+    #[inline]
+    fn show(&mut self, frame: &Frame) {
+        self.frame = *frame;
+    }
 }
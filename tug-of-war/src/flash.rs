@@ -0,0 +1,100 @@
+//! Settings and lifetime stats kept in the last pages of the on-chip flash.
+use core::ptr;
+use game_core::{
+    store::{Flash, Saved, Stats, Store},
+    Players, Settings,
+};
+use microbit::pac::NVMC;
+
+/// the flash after `FLASH` in `memory.x`.
+const STORE_START: u32 = 0x0007_c000;
+
+/// The flash pages of the store, written through the NVMC.
+pub struct NvmcFlash(NVMC);
+
+impl NvmcFlash {
+    #[inline]
+    fn wait_ready(&self) {
+        while self.0.ready.read().ready().is_busy() {}
+    }
+}
+
+impl Flash for NvmcFlash {
+    const PAGE_SIZE: u32 = 4096;
+    const PAGES: u32 = 4;
+
+    #[inline]
+    fn read_word(&mut self, offset: u32) -> u32 {
+        unsafe { ptr::read_volatile((STORE_START + offset) as *const u32) }
+    }
+
+    fn write_word(&mut self, offset: u32, word: u32) {
+        self.0.config.write(|w| w.wen().wen());
+        unsafe { ptr::write_volatile((STORE_START + offset) as *mut u32, word) };
+        self.wait_ready();
+        self.0.config.write(|w| w.wen().ren());
+    }
+
+    fn erase_page(&mut self, page: u32) {
+        self.0.config.write(|w| w.wen().een());
+        self.0
+            .erasepage()
+            .write(|w| unsafe { w.bits(STORE_START + page * Self::PAGE_SIZE) });
+        self.wait_ready();
+        self.0.config.write(|w| w.wen().ren());
+    }
+}
+
+/// The stats saved with the settings, and the record left for the
+/// main loop to write, an erase stalling the CPU for tens of
+/// milliseconds.
+pub struct Persist {
+    stats: Stats,
+    pending: Option<Saved>,
+}
+
+impl Persist {
+    /// open the store, returns it with the saved settings if any.
+    pub fn open(nvmc: NVMC) -> (Self, Store<NvmcFlash>, Option<Settings>) {
+        let (store, saved) = Store::open(NvmcFlash(nvmc));
+        let stats = saved.map_or(Stats::new(), |saved| saved.stats);
+        crate::debug::info!("lifetime stats: {:?}", stats);
+        let persist = Self {
+            stats,
+            pending: None,
+        };
+        (persist, store, saved.map(|saved| saved.settings))
+    }
+
+    /// count a round won by `winner`.
+    #[inline]
+    pub fn add(&mut self, winner: Players) {
+        self.stats.add(winner);
+    }
+
+    #[inline]
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// forget the stats, saved with the next settings.
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::new();
+    }
+
+    /// save the settings with the stats, once taken by the main loop.
+    #[inline]
+    pub fn save(&mut self, settings: &Settings) {
+        self.pending = Some(Saved {
+            settings: *settings,
+            stats: self.stats,
+        });
+    }
+
+    /// the record to write to the store, the latest if saved twice.
+    #[inline]
+    pub fn take_pending(&mut self) -> Option<Saved> {
+        self.pending.take()
+    }
+}
@@ -1,17 +1,30 @@
 #![no_main]
 #![no_std]
+use core::{cmp::Reverse, fmt::Write as _};
+use cortex_m::interrupt::CriticalSection;
 use cortex_m_rt::entry;
-use game::Game;
+#[cfg(feature = "radio")]
+use game_core::{
+    broadcast::{Snapshot, INTERVAL},
+    link::{Host, Transport as _},
+};
+use game_core::{
+    console::{self, Command, HELP},
+    replay::Recorder,
+    store::{Stats, Store},
+    telemetry::Event,
+    Chord, DotState, Duration, EventQueue, Game, Gesture, InputEvent, Instant, LatchedInput,
+    LogoAction, Match, Players, RandomSource, Settings, SoundSink as _, TimerMode,
+};
 use microbit::{
-    board::Buttons,
-    gpio::{DisplayPins, NUM_COLS, NUM_ROWS},
+    gpio::{NUM_COLS, NUM_ROWS},
     hal::{
         gpio::{Disconnected, Pin, PushPull},
         gpiote::Gpiote,
         rtc::RtcInterrupt,
         Clocks, Rng, Rtc,
     },
-    pac::{interrupt, CLOCK, GPIOTE, NVIC, PWM0, RNG, RTC0},
+    pac::{interrupt, CLOCK, NVIC, NVMC, PWM0, RNG, RTC0},
     Board,
 };
 #[cfg(not(debug_assertions))]
@@ -22,34 +35,88 @@ use panic_rtt_target as _;
 use rtt_target::rtt_init_print;
 
 mod buttons;
+mod clock;
 mod debug;
 mod display;
-mod game;
+mod flash;
 mod notes;
+#[cfg(feature = "radio")]
+mod radio;
+mod serial;
 mod sound;
-mod spiral;
+mod touch;
 use buttons::*;
+use clock::*;
 use display::*;
 use embed_mutex::*;
+use flash::*;
 use notes::*;
+#[cfg(feature = "radio")]
+use radio::*;
+use serial::*;
 use sound::*;
-use spiral::*;
+use touch::*;
 
-static RTC: Mutex<Rtc<RTC0>> = Mutex::new_uinit();
-static GAME: Mutex<Game> = Mutex::new(Game::new());
+static RTC: Mutex<RtcClock> = Mutex::new_uinit();
+static GAME: Mutex<Match> = Mutex::new(Match::new(SETTINGS));
 static DEVICE: Mutex<Device> = Mutex::new_uinit();
+static RECORDER: Mutex<Recorder<LOG_SIZE>> = Mutex::new(Recorder::new());
+static STORE: Mutex<Persist> = Mutex::new_uinit();
+/// events of the button and sound interrupts, and of the remote boards,
+/// drained each tick.
+static EVENTS: EventQueue<InputEvent, 32> = EventQueue::new();
+/// telemetry of the game tick, sent over the UART between interrupts.
+static TELEMETRY: EventQueue<Event, 32> = EventQueue::new();
+
+/// settings are saved once unchanged for this long, to spare the flash.
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+/// bytes of match log for a round.
+const LOG_SIZE: usize = 2048;
+/// settings of the game.
+const SETTINGS: Settings = Settings::new();
+/// what a touch of the logo does.
+const LOGO_ACTION: LogoAction = LogoAction::Pause;
 
 type DisplayPinsArray = (
     [microbit::hal::gpio::Pin<microbit::hal::gpio::Output<PushPull>>; NUM_COLS],
     [microbit::hal::gpio::Pin<microbit::hal::gpio::Output<PushPull>>; NUM_ROWS],
 );
 
+/// buttons of the two teams, with `team-play` a second button per team
+/// on pins 1 and 2 of the edge connector, for two versus two.
+#[cfg(not(feature = "team-play"))]
+const BUTTONS: usize = 2;
+#[cfg(feature = "team-play")]
+const BUTTONS: usize = 4;
+
 /// Devices used for the game.
-struct Device {
-    buttons: ButtonState,
-    display: DisplayPinsArray,
-    sound: Sound,
+type Device = game_core::Device<ButtonState<BUTTONS>, LedDriver, Sound, HwRng>;
+
+/// Hardware random number generator.
+struct HwRng {
     rng: Rng,
+    /// byte drawn during the last poll, for recording.
+    drawn: Option<u8>,
+}
+
+impl RandomSource for HwRng {
+    #[inline]
+    fn random_u8(&mut self) -> u8 {
+        let value = self.rng.random_u8();
+        self.drawn = Some(value);
+        value
+    }
+}
+
+/// Random bytes for the computer opponent, not recorded,
+/// since its presses are.
+struct BotRng<'a>(&'a mut Rng);
+
+impl RandomSource for BotRng<'_> {
+    #[inline]
+    fn random_u8(&mut self) -> u8 {
+        self.0.random_u8()
+    }
 }
 
 #[entry]
@@ -57,11 +124,40 @@ fn main() -> ! {
     #[cfg(debug_assertions)]
     rtt_init_print!();
     let board = Board::take().unwrap();
-    init_rtc(board.CLOCK, board.RTC0);
+    // not handed out by the board
+    let nvmc = unsafe { microbit::pac::Peripherals::steal() }.NVMC;
+    let mut flash = init_store(nvmc);
+    let clocks = init_rtc(board.CLOCK, board.RTC0);
     init_device(
-        board.display_pins,
-        board.buttons,
-        board.GPIOTE,
+        LedDriver::new(board.display_pins.degrade(), board.TIMER1),
+        ButtonState::new(
+            [
+                Button::new(
+                    board.buttons.button_a.into_pullup_input().degrade(),
+                    Players::A,
+                    0,
+                ),
+                Button::new(
+                    board.buttons.button_b.into_pullup_input().degrade(),
+                    Players::B,
+                    0,
+                ),
+                #[cfg(feature = "team-play")]
+                Button::new(
+                    board.pins.p0_03.into_pullup_input().degrade(),
+                    Players::A,
+                    1,
+                ),
+                #[cfg(feature = "team-play")]
+                Button::new(
+                    board.pins.p0_04.into_pullup_input().degrade(),
+                    Players::B,
+                    1,
+                ),
+            ],
+            Gpiote::new(board.GPIOTE),
+            TouchLogo::new(board.pins.p1_04.degrade()),
+        ),
         board.RNG,
         #[cfg(not(feature = "output-sound"))]
         board.speaker_pin.degrade(),
@@ -71,29 +167,180 @@ fn main() -> ! {
         board.NVIC,
     );
     crate::debug::info!("device initialized");
+    let mut serial = Serial::new(board.UARTE0, board.uart);
+    #[cfg(feature = "radio")]
+    let mut host = Host::new(RadioLink::new(board.RADIO, board.TIMER2, clocks));
+    #[cfg(feature = "radio")]
+    let mut broadcast = Instant::default();
+    #[cfg(not(feature = "radio"))]
+    let _ = clocks;
     loop {
+        while let Some(event) = TELEMETRY.pop() {
+            serial.send(&event);
+        }
+        poll_console(&mut serial);
+        write_pending(&mut flash);
+        #[cfg(feature = "radio")]
+        poll_radio(&mut host, &mut broadcast);
+        // until the next interrupt, of a tick, a button, a byte of the
+        // console, or a frame received by the radio
         cortex_m::asm::wfi();
     }
 }
 
-/// initialize a 256HZ RTC clock.
-fn init_rtc(clock: CLOCK, rtc0: RTC0) {
+/// listen to the remote boards of the players, and broadcast the match
+/// to the spectators, between interrupts.
+#[cfg(feature = "radio")]
+fn poll_radio(host: &mut Host<RadioLink>, broadcast: &mut Instant) {
+    let now = || cortex_m::interrupt::free(|cs| RTC.try_lock(cs).map(|rtc| rtc.now()));
+    while let Some(event) = now().and_then(|now| host.poll(now)) {
+        cortex_m::interrupt::free(|_| EVENTS.push(event));
+    }
+    let Some(now) = now() else {
+        return;
+    };
+    if now.since(*broadcast) < INTERVAL {
+        return;
+    }
+    *broadcast = now;
+    let snapshot = cortex_m::interrupt::free(|cs| GAME.try_lock(cs).map(|m| Snapshot::of(&m)));
+    if let Some(snapshot) = snapshot {
+        host.transport().send(&snapshot.encode());
+    }
+}
+
+/// What a command of the console replies.
+enum Reply {
+    Done,
+    Settings(Settings, u8),
+    Stats(Stats),
+    /// the devices are not initialized yet.
+    Busy,
+}
+
+/// run the commands of the console received since the last poll, and
+/// reply to each, outside of the critical section.
+fn poll_console(serial: &mut Serial) {
+    while let Some(command) = serial.receive() {
+        let reply = match command {
+            Ok(Command::Help) => {
+                serial.reply(|out| out.write_str(HELP));
+                continue;
+            }
+            Ok(Command::Log) => {
+                // copied out, to print it outside of the critical section
+                let mut log = [0; LOG_SIZE];
+                let copied = cortex_m::interrupt::free(|cs| {
+                    RECORDER.try_lock(cs).map(|recorder| {
+                        let len = recorder.log().len();
+                        log[..len].copy_from_slice(recorder.log());
+                        (len, recorder.overflowed())
+                    })
+                });
+                serial.reply(|out| match copied {
+                    Some((len, overflow)) => console::write_log(&log[..len], overflow, out),
+                    None => writeln!(out, "error: busy"),
+                });
+                continue;
+            }
+            Ok(Command::Telemetry(on)) => {
+                serial.telemetry = on;
+                Reply::Done
+            }
+            Ok(command) => cortex_m::interrupt::free(|cs| run_command(cs, command)),
+            Err(e) => {
+                serial.reply(|out| writeln!(out, "error: {}", e.message()));
+                continue;
+            }
+        };
+        serial.reply(|out| match reply {
+            Reply::Done => writeln!(out, "ok"),
+            Reply::Settings(settings, volume) => console::write_settings(&settings, volume, out),
+            Reply::Stats(stats) => console::write_stats(&stats, out),
+            Reply::Busy => writeln!(out, "error: busy"),
+        });
+    }
+}
+
+/// run a command of the console on the game, settings are saved like
+/// the ones of the menu.
+fn run_command(cs: &CriticalSection, command: Command) -> Reply {
+    let (Some(mut device), Some(mut rtc), Some(mut game), Some(store)) = (
+        DEVICE.try_lock(cs),
+        RTC.try_lock(cs),
+        GAME.try_lock(cs),
+        STORE.try_lock(cs),
+    ) else {
+        return Reply::Busy;
+    };
+    match command {
+        Command::Get => return Reply::Settings(*game.settings(), device.sound.volume()),
+        Command::Set(setting) => {
+            let mut settings = *game.settings();
+            setting.apply(&mut settings);
+            game.set_settings(settings);
+            rtc.start(TimerEvent::SaveSettings, SAVE_DELAY, TimerMode::OneShot);
+        }
+        Command::Volume(volume) => device.sound.set_volume(volume),
+        Command::Stats => return Reply::Stats(*store.stats()),
+        Command::Play(track) => device.sound.play_track(track),
+        Command::Reset => {
+            device.sound.stop();
+            *game = Match::new(*game.settings());
+        }
+        Command::Telemetry(_) | Command::Log | Command::Help => (),
+    }
+    Reply::Done
+}
+
+/// initialize STORE, and the game with the saved settings, returns the
+/// store written by the main loop.
+fn init_store(nvmc: NVMC) -> Store<NvmcFlash> {
+    let (persist, store, settings) = Persist::open(nvmc);
+    cortex_m::interrupt::free(|cs| {
+        STORE.init(cs, persist);
+        if let (Some(settings), Some(mut game)) = (settings, GAME.try_lock(cs)) {
+            game.set_settings(settings);
+        }
+    });
+    store
+}
+
+/// write the settings and stats saved by the game tick, outside of the
+/// critical section, so that the erase of a page does not hold it.
+fn write_pending(flash: &mut Store<NvmcFlash>) {
+    let pending = cortex_m::interrupt::free(|cs| {
+        STORE
+            .try_lock(cs)
+            .and_then(|mut store| store.take_pending())
+    });
+    if let Some(saved) = pending {
+        flash.save(&saved);
+    }
+}
+
+/// initialize a 256HZ RTC clock, returns the clocks, with the crystal
+/// only when the radio needs it.
+fn init_rtc(clock: CLOCK, rtc0: RTC0) -> &'static HfClocks {
     // 256HZ (32768 / 256 - 1)
     const RTC_PRESCALER: u32 = 127u32;
 
-    Clocks::new(clock).set_lfclk_src_rc().start_lfclk();
+    let clocks = Clocks::new(clock);
+    #[cfg(feature = "radio")]
+    let clocks = clocks.enable_ext_hfosc();
+    let clocks = clocks.set_lfclk_src_rc().start_lfclk();
     let mut rtc0 = Rtc::new(rtc0, RTC_PRESCALER).unwrap();
     rtc0.enable_event(RtcInterrupt::Tick);
     rtc0.enable_interrupt(RtcInterrupt::Tick, None);
     rtc0.enable_counter();
-    cortex_m::interrupt::free(|cs| RTC.init(cs, rtc0));
+    cortex_m::interrupt::free(|cs| RTC.init(cs, RtcClock::new(rtc0)));
+    cortex_m::singleton!(: HfClocks = clocks).unwrap()
 }
 
 /// initialize DEVICE variable.
 fn init_device(
-    display: DisplayPins,
-    buttons: Buttons,
-    gpiote: GPIOTE,
+    display: LedDriver,
+    mut buttons: ButtonState<BUTTONS>,
     rng: RNG,
     speaker: Pin<Disconnected>,
     pwm: PWM0,
@@ -101,21 +348,28 @@ fn init_device(
 ) {
     // enable interrupts
     unsafe {
+        nvic.set_priority(interrupt::TIMER1, 0);
         nvic.set_priority(interrupt::RTC0, 32);
         nvic.set_priority(interrupt::GPIOTE, 64);
+        nvic.set_priority(interrupt::UARTE0_UART0, 96);
         nvic.set_priority(interrupt::PWM0, 128);
+        NVIC::unmask(interrupt::TIMER1);
         NVIC::unmask(interrupt::RTC0);
         NVIC::unmask(interrupt::GPIOTE);
+        NVIC::unmask(interrupt::UARTE0_UART0);
         NVIC::unmask(interrupt::PWM0);
+        #[cfg(feature = "radio")]
+        {
+            nvic.set_priority(interrupt::RADIO, 160);
+            NVIC::unmask(interrupt::RADIO);
+        }
     }
-    let buttons = ButtonState::new(
-        buttons.button_a.degrade(),
-        buttons.button_b.degrade(),
-        Gpiote::new(gpiote),
-    );
-    let display = display.degrade();
+    buttons.bind_logo(LOGO_ACTION);
     let sound = Sound::init(pwm, speaker);
-    let rng = Rng::new(rng);
+    let rng = HwRng {
+        rng: Rng::new(rng),
+        drawn: None,
+    };
     cortex_m::interrupt::free(|cs| {
         DEVICE.init(
             cs,
@@ -129,14 +383,155 @@ fn init_device(
     });
 }
 
-/// main interrupt to drive display and game progress.
+/// main interrupt to drive game progress, and fire the timers.
 #[interrupt]
 fn RTC0() {
     cortex_m::interrupt::free(|cs| {
-        if let (Some(mut device), Some(mut rtc), Some(mut game)) =
-            (DEVICE.try_lock(cs), RTC.try_lock(cs), GAME.try_lock(cs))
-        {
-            game.poll(&mut rtc, &mut device);
+        if let (
+            Some(mut device),
+            Some(mut rtc),
+            Some(mut game),
+            Some(mut recorder),
+            Some(mut store),
+        ) = (
+            DEVICE.try_lock(cs),
+            RTC.try_lock(cs),
+            GAME.try_lock(cs),
+            RECORDER.try_lock(cs),
+            STORE.try_lock(cs),
+        ) {
+            if rtc.is_tick() {
+                game_tick(&mut device, &mut rtc, &mut game, &mut recorder, &mut store);
+            }
+            while let Some(event) = rtc.poll_timers() {
+                match event {
+                    TimerEvent::SaveSettings => store.save(game.settings()),
+                }
+            }
+        }
+    });
+}
+
+/// poll the game, record its inputs, keep the stats, and queue the
+/// telemetry.
+fn game_tick(
+    device: &mut Device,
+    rtc: &mut RtcClock,
+    game: &mut Match,
+    recorder: &mut Recorder<LOG_SIZE>,
+    store: &mut Persist,
+) {
+    if let Some(event) = device.buttons.poll_logo(rtc.now()) {
+        EVENTS.push(event);
+    }
+    // inputs arrived before this tick, in the order they happened, the
+    // edges of the remotes stamped back by their latency
+    let now = rtc.now();
+    EVENTS.drain_by_key(
+        |event| Reverse(event.at().map_or(Duration::default(), |at| now.since(at))),
+        |event| {
+            if let Some(input) = device.buttons.handle_event(&event) {
+                record(recorder, input, event.at().unwrap_or(now));
+            }
+        },
+    );
+    while let Some(input) = device.buttons.poll(rtc.now()) {
+        record(recorder, input, rtc.now());
+    }
+    if matches!(game.game(), Game::IdleAnimation { .. })
+        && device.buttons.gesture() == Some(Gesture::LongPress(Chord::AB))
+    {
+        crate::debug::info!("stats reset");
+        store.reset_stats();
+        store.save(game.settings());
+    }
+    recorder.tick();
+    let settings = *game.settings();
+    // the count down restarts after a false start penalty
+    let was_waiting = matches!(
+        game.game(),
+        Game::IdleAnimation { .. } | Game::Result { .. }
+    );
+    let was_ready = matches!(game.game(), Game::ReadyAnimation { .. });
+    let position = game.game().dot().map(DotState::position);
+    let round_winner = game.poll(rtc, device);
+    if let Some(value) = device.rng.drawn.take() {
+        recorder.random(value);
+    }
+    if let Game::Playing { dot, .. } = game.game() {
+        let at = rtc.now();
+        if was_ready {
+            TELEMETRY.push(Event::Go { at });
+        } else if Some(dot.position()) != position {
+            TELEMETRY.push(Event::Step {
+                at,
+                position: dot.position(),
+            });
+        }
+    }
+    if let Some(winner) = round_winner {
+        TELEMETRY.push(Event::Result {
+            at: rtc.now(),
+            winner,
+            score: game.score(),
+        });
+        store.add(winner);
+        rtc.cancel(TimerEvent::SaveSettings);
+        store.save(game.settings());
+    } else if *game.settings() != settings {
+        rtc.start(TimerEvent::SaveSettings, SAVE_DELAY, TimerMode::OneShot);
+    }
+    // like a press arriving before the next tick
+    let playing = matches!(game.game(), Game::Playing { .. });
+    let Device { buttons, rng, .. } = device;
+    if let Some((player, other_held)) =
+        buttons.poll_bot(game.settings().bot, playing, &mut BotRng(&mut rng.rng))
+    {
+        recorder.press(player, other_held);
+        TELEMETRY.push(Event::Press {
+            at: rtc.now(),
+            player,
+        });
+    }
+    match game.game() {
+        Game::ReadyAnimation { .. } if was_waiting => {
+            recorder.start(game.settings());
+            TELEMETRY.push(Event::RoundStart {
+                at: rtc.now(),
+                settings: *game.settings(),
+                score: game.score(),
+            });
+        }
+        Game::Result { .. } if round_winner.is_some() => {
+            crate::debug::info!(
+                "match log (overflow = {}, events dropped = {}): {:02x?}",
+                recorder.overflowed(),
+                EVENTS.overflows(),
+                recorder.log()
+            );
+        }
+        _ => (),
+    }
+}
+
+/// record an input taken at `at`, and queue the presses as telemetry.
+#[inline]
+fn record(recorder: &mut Recorder<LOG_SIZE>, input: LatchedInput, at: Instant) {
+    match input {
+        LatchedInput::Press { player, other_held } => {
+            recorder.press(player, other_held);
+            TELEMETRY.push(Event::Press { at, player });
+        }
+        LatchedInput::Gesture(gesture) => recorder.gesture(gesture),
+    }
+}
+
+/// interrupt for multiplexing the display.
+#[interrupt]
+fn TIMER1() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(mut device) = DEVICE.try_lock(cs) {
+            device.display.handle_interrupt();
         }
     });
 }
@@ -146,17 +541,38 @@ fn RTC0() {
 fn PWM0() {
     cortex_m::interrupt::free(|cs| {
         if let Some(mut device) = DEVICE.try_lock(cs) {
-            device.sound.handle_interrupt();
+            if let Some(track) = device.sound.handle_interrupt() {
+                EVENTS.push(InputEvent::TrackFinished(track));
+            }
         }
     });
 }
 
+/// interrupt of the radio, waking the main loop once a frame is
+/// received, which the next poll of the remotes reads.
+#[cfg(feature = "radio")]
+#[interrupt]
+fn RADIO() {
+    unsafe { &*microbit::pac::RADIO::ptr() }
+        .intenclr
+        .write(|w| w.end().clear());
+}
+
+/// interrupt of the serial port, queueing a byte received, which the
+/// next poll of the console reads.
+#[interrupt]
+fn UARTE0_UART0() {
+    serial::handle_interrupt();
+}
+
 /// interrupt for buttons.
 #[interrupt]
 fn GPIOTE() {
     cortex_m::interrupt::free(|cs| {
-        if let Some(mut device) = DEVICE.try_lock(cs) {
-            device.buttons.handle_interrupt();
+        if let (Some(mut device), Some(rtc)) = (DEVICE.try_lock(cs), RTC.try_lock(cs)) {
+            if let Some(event) = device.buttons.handle_interrupt(rtc.now()) {
+                EVENTS.push(event);
+            }
         }
     });
 }
@@ -1,4 +1,6 @@
 //! notes
+use game_core::Track;
+
 #[link_section = ".notes"]
 static B1: [u16; 127] = [
     0x40, 0x43, 0x46, 0x49, 0x4c, 0x4f, 0x52, 0x55, 0x58, 0x5b, 0x5e, 0x61, 0x63, 0x66, 0x68, 0x6b,
@@ -100,3 +102,24 @@ pub static PEPPA: [Note; 12] = [
     (&E1, 500),
     (&C1, 500),
 ];
+pub static FANFARE: [Note; 7] = [
+    (&C1, 250),
+    (&E1, 250),
+    (&G1, 250),
+    (&C2, 500),
+    (&SI, 125),
+    (&G1, 250),
+    (&C2, 1000),
+];
+pub static BUZZ: [Note; 5] = [(&G0, 100), (&SI, 50), (&G0, 100), (&SI, 50), (&G0, 300)];
+
+/// notes of a game track.
+pub fn track_notes(track: Track) -> Notes {
+    match track {
+        Track::DiLo => &DI_LO,
+        Track::DiHi => &DI_HI,
+        Track::Peppa => &PEPPA,
+        Track::Fanfare => &FANFARE,
+        Track::Buzz => &BUZZ,
+    }
+}
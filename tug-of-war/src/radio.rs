@@ -0,0 +1,126 @@
+//! The 2.4GHz radio carrying the link between the boards.
+use core::sync::atomic::{compiler_fence, Ordering};
+use game_core::link::{Transport, MAX_FRAME};
+use microbit::{
+    hal::{
+        clocks::{Clocks, ExternalOscillator},
+        ieee802154::{Channel, Packet, Radio},
+        Timer,
+    },
+    pac::{radio::RegisterBlock, RADIO, TIMER2},
+};
+
+/// channel shared by the boards, away from the busiest WiFi channels.
+const CHANNEL: Channel = Channel::_20;
+/// microseconds of the first reception, which readies the receiver.
+const READY_US: u32 = 200;
+
+/// Frames of the link, over IEEE 802.15.4 packets.
+///
+/// the receiver stays on between the polls of `recv`, and a frame
+/// received raises the RADIO interrupt, so that the board can sleep
+/// meanwhile. The HAL only receives blocking, so the receptions are
+/// started on the registers, into the packet the HAL last used.
+pub struct RadioLink {
+    radio: Radio<'static>,
+    /// in RAM for the DMA, at the same place for every reception.
+    packet: &'static mut Packet,
+    /// a reception is started, and not stopped to send.
+    listening: bool,
+}
+
+/// the registers of the radio, shared with the HAL.
+#[inline]
+fn registers() -> &'static RegisterBlock {
+    unsafe { &*RADIO::ptr() }
+}
+
+impl RadioLink {
+    /// the radio needs the crystal oscillator.
+    pub fn new<L, S>(
+        radio: RADIO,
+        timer: TIMER2,
+        clocks: &'static Clocks<ExternalOscillator, L, S>,
+    ) -> Self {
+        let mut radio = Radio::init(radio, clocks);
+        radio.set_channel(CHANNEL);
+        let packet = cortex_m::singleton!(: Packet = Packet::new()).unwrap();
+        // enables the receiver on the channel, and points it at the packet
+        let _ = radio.recv_timeout(packet, &mut Timer::new(timer), READY_US);
+        Self {
+            radio,
+            packet,
+            listening: false,
+        }
+    }
+
+    /// keep receiving until a frame ends, which raises the interrupt.
+    fn listen(&mut self) {
+        if self.listening {
+            return;
+        }
+        let radio = registers();
+        // disabled by the HAL after sending
+        loop {
+            let state = radio.state.read().state();
+            if state.is_rx_idle() {
+                break;
+            }
+            if state.is_disabled() {
+                radio.tasks_rxen.write(|w| w.tasks_rxen().set_bit());
+            } else if state.is_tx_idle() {
+                radio.tasks_disable.write(|w| w.tasks_disable().set_bit());
+            }
+        }
+        radio.events_end.reset();
+        radio.intenset.write(|w| w.end().set());
+        compiler_fence(Ordering::Release);
+        radio.tasks_start.write(|w| w.tasks_start().set_bit());
+        self.listening = true;
+    }
+
+    /// stop receiving, before the HAL takes the radio to send.
+    fn stop(&mut self) {
+        if !core::mem::take(&mut self.listening) {
+            return;
+        }
+        let radio = registers();
+        radio.intenclr.write(|w| w.end().clear());
+        radio.tasks_stop.write(|w| w.tasks_stop().set_bit());
+        while !radio.state.read().state().is_rx_idle() {}
+        radio.events_end.reset();
+        compiler_fence(Ordering::Acquire);
+    }
+}
+
+impl Transport for RadioLink {
+    /// waits for the channel to be clear, then listens again on the
+    /// next `recv`.
+    fn send(&mut self, frame: &[u8]) {
+        self.stop();
+        self.packet.copy_from_slice(frame);
+        self.radio.send(self.packet);
+    }
+
+    /// the frame received since the last call, frames failing the CRC
+    /// are dropped, and listens for the next one.
+    fn recv(&mut self, buf: &mut [u8; MAX_FRAME]) -> Option<usize> {
+        let radio = registers();
+        if !self.listening || radio.events_end.read().bits() == 0 {
+            self.listen();
+            return None;
+        }
+        radio.events_end.reset();
+        compiler_fence(Ordering::Acquire);
+        self.listening = false;
+        let frame = match radio.crcstatus.read().crcstatus().bit_is_set() {
+            true => buf.get_mut(..self.packet.len() as usize).map(|frame| {
+                frame.copy_from_slice(self.packet);
+                frame.len()
+            }),
+            false => None,
+        };
+        self.listen();
+        frame
+    }
+}
@@ -0,0 +1,141 @@
+//! The UART of the board, a serial port of the host through the USB
+//! interface chip, streaming the telemetry of the game, and taking the
+//! commands of the console.
+use core::{
+    fmt,
+    sync::atomic::{compiler_fence, AtomicU8, Ordering},
+};
+use game_core::{
+    console::{Command, LineReader, ParseError},
+    telemetry::{Event, MAX_FRAME},
+    EventQueue,
+};
+use microbit::{
+    board::UartPins,
+    hal::{
+        prelude::_embedded_hal_blocking_serial_Write as _,
+        uarte::{Baudrate, Parity, Uarte, UarteTx},
+    },
+    pac::{uarte0::RegisterBlock, UARTE0},
+};
+
+/// bytes received, until the main loop parses them, two full lines.
+static RECEIVED: EventQueue<u8, 64> = EventQueue::new();
+/// the byte the DMA receives, in RAM.
+static RX_BYTE: AtomicU8 = AtomicU8::new(0);
+
+/// Telemetry frames and console lines over the UART, at 115200 baud, 8N1.
+///
+/// The HAL only receives blocking, so each byte is received on the
+/// registers, and queued by the UARTE0 interrupt. The UARTE keeps the
+/// next 4 bytes until the interrupt starts receiving again.
+pub struct Serial {
+    tx: UarteTx<UARTE0>,
+    lines: LineReader,
+    /// send the telemetry, muted from the console.
+    pub telemetry: bool,
+}
+
+impl Serial {
+    pub fn new(uarte: UARTE0, pins: UartPins) -> Self {
+        let uarte = Uarte::new(uarte, pins.into(), Parity::EXCLUDED, Baudrate::BAUD115200);
+        // the DMA reads and writes these, in RAM
+        let tx_buf = cortex_m::singleton!(: [u8; MAX_FRAME] = [0; MAX_FRAME]).unwrap();
+        let rx_buf = cortex_m::singleton!(: [u8; 1] = [0; 1]).unwrap();
+        let (tx, _) = uarte.split(tx_buf, rx_buf).unwrap();
+        let uarte = registers();
+        uarte
+            .rxd
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(RX_BYTE.as_ptr() as u32) });
+        uarte.rxd.maxcnt.write(|w| unsafe { w.maxcnt().bits(1) });
+        uarte.intenset.write(|w| w.endrx().set().error().set());
+        start_rx(uarte);
+        Self {
+            tx,
+            lines: LineReader::new(),
+            telemetry: true,
+        }
+    }
+
+    /// blocks until the frame is out, about 1.5ms for the longest.
+    pub fn send(&mut self, event: &Event) {
+        if !self.telemetry {
+            return;
+        }
+        let mut frame = [0; MAX_FRAME];
+        let len = event.encode(&mut frame);
+        let _ = self.tx.bwrite_all(&frame[..len]);
+        let _ = self.tx.bflush();
+    }
+
+    /// the next command received, if any, the rest of a line is kept
+    /// until the next poll.
+    pub fn receive(&mut self) -> Option<Result<Command, ParseError>> {
+        while let Some(byte) = RECEIVED.pop() {
+            if let Some(command) = self.lines.push(byte) {
+                return Some(command);
+            }
+        }
+        None
+    }
+
+    /// write a reply of the console, ended by a zero byte, so that the
+    /// telemetry decoder drops it as a bad frame and syncs again.
+    pub fn reply(&mut self, write: impl FnOnce(&mut Self) -> fmt::Result) {
+        let _ = write(self);
+        let _ = self.tx.bwrite_all(&[0]);
+        let _ = self.tx.bflush();
+    }
+}
+
+impl fmt::Write for Serial {
+    /// lines end with `\r\n`, for terminals.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (index, line) in s.split('\n').enumerate() {
+            if index > 0 {
+                self.tx.bwrite_all(b"\r\n").map_err(|_| fmt::Error)?;
+            }
+            self.tx
+                .bwrite_all(line.as_bytes())
+                .map_err(|_| fmt::Error)?;
+        }
+        Ok(())
+    }
+}
+
+/// the registers of the UARTE, shared with the HAL sending.
+#[inline]
+fn registers() -> &'static RegisterBlock {
+    unsafe { &*UARTE0::ptr() }
+}
+
+/// receive the next byte, which raises the interrupt.
+#[inline]
+fn start_rx(uarte: &RegisterBlock) {
+    uarte.events_endrx.reset();
+    compiler_fence(Ordering::Release);
+    uarte.tasks_startrx.write(|w| w.tasks_startrx().set_bit());
+}
+
+/// queue the byte received, and receive the next one.
+///
+/// bytes with a framing error or an overrun are lost, as are the ones
+/// received while the queue is full, failing the command of their line.
+pub fn handle_interrupt() {
+    let uarte = registers();
+    if uarte.events_error.read().bits() != 0 {
+        uarte.events_error.reset();
+        let errors = uarte.errorsrc.read().bits();
+        // written back to clear them
+        uarte.errorsrc.write(|w| unsafe { w.bits(errors) });
+    }
+    if uarte.events_endrx.read().bits() == 0 {
+        return;
+    }
+    compiler_fence(Ordering::Acquire);
+    if uarte.rxd.amount.read().amount().bits() > 0 {
+        RECEIVED.push(RX_BYTE.load(Ordering::Relaxed));
+    }
+    start_rx(uarte);
+}
@@ -6,18 +6,25 @@ use microbit::{
     pac::PWM0,
 };
 
-use crate::{Note, Notes};
+use crate::{track_notes, Note, Notes};
+use core::ptr;
+use game_core::{console::MAX_VOLUME, SoundSink, Track};
 
 const MAX_DUTY: u16 = 256;
 const SAMPLE_FREQ: u16 = 62500;
 const CHANNEL: pwm::Channel = pwm::Channel::C0;
+/// samples of the longest note.
+const MAX_SAMPLES: usize = 160;
 
-struct Track {
+/// the note played below full volume, scaled down, in RAM for the DMA.
+static mut SCALED: [u16; MAX_SAMPLES] = [0; MAX_SAMPLES];
+
+struct Playback {
     notes: Notes,
     position: usize,
 }
 
-impl Track {
+impl Playback {
     fn next_note(&mut self) -> Option<Note> {
         self.notes.get(self.position).map(|note| {
             self.position += 1;
@@ -29,11 +36,15 @@ impl Track {
 pub struct Sound {
     pwm: Option<pwm::Pwm<PWM0>>,
     state: AudioState,
+    /// track played by `play_track`.
+    track: Option<Track>,
+    /// 0 to `MAX_VOLUME`.
+    volume: u8,
 }
 
 enum AudioState {
     Disconnected { speaker: Pin<Disconnected> },
-    Playing { track: Track },
+    Playing { playback: Playback },
 }
 
 impl Sound {
@@ -51,19 +62,33 @@ impl Sound {
         Self {
             pwm: Some(pwm),
             state,
+            track: None,
+            volume: MAX_VOLUME,
         }
     }
 
-    /// set track and start playing.
+    #[inline]
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    /// scale the duty of the notes from the next one on.
+    #[inline]
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume.min(MAX_VOLUME);
+    }
+
+    /// set notes and start playing.
     /// If currently playing, stop this track.
-    pub fn play_track(&mut self, notes: Notes) {
+    pub fn play_notes(&mut self, notes: Notes) {
+        self.track = None;
         // initialize to 1 since note[0] is immediately triggered.
         const INIT_PLAY_POS: usize = 1;
         // set new state to Playing
         let state = core::mem::replace(
             &mut self.state,
             AudioState::Playing {
-                track: Track {
+                playback: Playback {
                     notes,
                     position: INIT_PLAY_POS,
                 },
@@ -86,19 +111,24 @@ impl Sound {
         }
         // play the first note
         let pwm = self.pwm.take().unwrap();
-        self.pwm.replace(Self::play_note(pwm, notes[0]));
+        self.pwm
+            .replace(Self::play_note(pwm, notes[0], self.volume));
     }
 
     /// handles LOOPS_DONE event.
-    pub fn handle_interrupt(&mut self) {
+    ///
+    /// returns the track once it has finished.
+    pub fn handle_interrupt(&mut self) -> Option<Track> {
         // reset event
         self.pwm_mut().reset_event(pwm::PwmEvent::LoopsDone);
 
-        if let AudioState::Playing { track } = &mut self.state {
-            if Self::play_next_note(track, &mut self.pwm) {
+        if let AudioState::Playing { playback } = &mut self.state {
+            if Self::play_next_note(playback, &mut self.pwm, self.volume) {
                 self.disconnect();
+                return self.track.take();
             }
         }
+        None
     }
 
     #[inline]
@@ -119,10 +149,14 @@ impl Sound {
     ///
     /// return done: bool
     #[inline]
-    fn play_next_note(track: &mut Track, pwm: &mut Option<pwm::Pwm<PWM0>>) -> bool {
-        if let Some(note) = track.next_note() {
+    fn play_next_note(
+        playback: &mut Playback,
+        pwm: &mut Option<pwm::Pwm<PWM0>>,
+        volume: u8,
+    ) -> bool {
+        if let Some(note) = playback.next_note() {
             let pwm_inner = pwm.take().unwrap();
-            pwm.replace(Self::play_note(pwm_inner, note));
+            pwm.replace(Self::play_note(pwm_inner, note, volume));
             false
         } else {
             true
@@ -131,13 +165,30 @@ impl Sound {
 
     /// Silent note is treated differently with refresh instead of loop.
     #[inline]
-    fn play_note(pwm: pwm::Pwm<PWM0>, (note, t_ms): Note) -> pwm::Pwm<PWM0> {
+    fn play_note(pwm: pwm::Pwm<PWM0>, (note, t_ms): Note, volume: u8) -> pwm::Pwm<PWM0> {
         pwm.repeat(Self::loops(t_ms, note.len()));
+        let note = Self::scaled(note, volume);
         let (s0, s1) = note.split_at(note.len() / 2);
         let (_, _, pwm) = pwm.load(Some(s0), Some(s1), true).unwrap().split();
         pwm
     }
 
+    /// the samples of a note with their duty scaled to the volume.
+    ///
+    /// The previous note is over or stopped, so the PWM no longer reads
+    /// `SCALED`.
+    fn scaled(note: &'static [u16], volume: u8) -> &'static [u16] {
+        if volume >= MAX_VOLUME {
+            return note;
+        }
+        let scaled = unsafe { &mut *ptr::addr_of_mut!(SCALED) };
+        let scaled = &mut scaled[..note.len().min(MAX_SAMPLES)];
+        for (sample, full) in scaled.iter_mut().zip(note) {
+            *sample = full * volume as u16 / MAX_VOLUME as u16;
+        }
+        scaled
+    }
+
     #[inline]
     fn pwm_mut(&mut self) -> &mut pwm::Pwm<PWM0> {
         self.pwm.as_mut().unwrap()
@@ -149,3 +200,18 @@ impl Sound {
         (t_ms as u32 * SAMPLE_FREQ as u32 / 1000_u32 / sample_len as u32) as u16
     }
 }
+
+impl SoundSink for Sound {
+    #[inline]
+    fn play_track(&mut self, track: Track) {
+        self.play_notes(track_notes(track));
+        self.track = Some(track);
+    }
+
+    fn stop(&mut self) {
+        if let AudioState::Playing { .. } = self.state {
+            self.disconnect();
+        }
+        self.track = None;
+    }
+}
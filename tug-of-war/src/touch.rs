@@ -0,0 +1,52 @@
+use game_core::{InputEvent, Instant, TouchDetector};
+use microbit::hal::{
+    gpio::{Disconnected, Level, Pin},
+    prelude::InputPin as _,
+};
+
+/// cycles to discharge the pad before a sample.
+const DISCHARGE_CYCLES: u32 = 64;
+/// longest charge time counted, when the pad does not charge.
+const MAX_CHARGE: u16 = 1024;
+
+/// Touch logo, sensed from the charge time of its pad.
+///
+/// the pad is discharged, then charged through its pull up resistor,
+/// a finger adds capacitance so it takes longer.
+pub struct TouchLogo {
+    /// the pad between samples, taken while sampling.
+    pad: Option<Pin<Disconnected>>,
+    detector: TouchDetector,
+}
+
+impl TouchLogo {
+    pub fn new(pad: Pin<Disconnected>) -> Self {
+        Self {
+            pad: Some(pad),
+            detector: TouchDetector::default(),
+        }
+    }
+
+    /// sample the pad, once per tick.
+    ///
+    /// returns the touch or release of the logo, sampled at `now`.
+    pub fn poll(&mut self, now: Instant) -> Option<InputEvent> {
+        let value = self.charge_time()?;
+        let touched = self.detector.sample(value)?;
+        crate::debug::info!("logo {}", touched);
+        Some(InputEvent::Logo { touched, at: now })
+    }
+
+    /// loops until the pad reads high.
+    fn charge_time(&mut self) -> Option<u16> {
+        let pad = self.pad.take()?.into_push_pull_output(Level::Low);
+        cortex_m::asm::delay(DISCHARGE_CYCLES);
+        let pad = pad.into_floating_input();
+        let mut time = 0;
+        while time < MAX_CHARGE && pad.is_low().unwrap() {
+            time += 1;
+        }
+        self.pad = Some(pad.into_disconnected());
+        Some(time)
+    }
+}